@@ -0,0 +1,37 @@
+/// The order in which bits are taken from octets and packed into quintets.
+///
+/// Z-base-32 is big-endian oriented, which corresponds to [`BitOrder::MsbFirst`].
+/// Some protocols instead pack bits starting from the lowest bit, which corresponds
+/// to [`BitOrder::LsbFirst`]. In both cases, it is the bits at the end of the
+/// bit stream that must be zero if they fall outside of the number of bits being
+/// encoded or decoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The first bit of each octet and of each quintet is its _highest_ bit.
+    ///
+    /// This is the bit order specified by z-base-32.
+    #[default]
+    MsbFirst,
+
+    /// The first bit of each octet and of each quintet is its _lowest_ bit.
+    LsbFirst,
+}
+
+impl BitOrder {
+    // Converting between the two bit orders just reverses the bits of every
+    // octet and every quintet - so, these conversions work in both directions.
+
+    pub(crate) fn convert_octet(self, octet: u8) -> u8 {
+        match self {
+            BitOrder::MsbFirst => octet,
+            BitOrder::LsbFirst => octet.reverse_bits(),
+        }
+    }
+
+    pub(crate) fn convert_quintet(self, quintet: u8) -> u8 {
+        match self {
+            BitOrder::MsbFirst => quintet,
+            BitOrder::LsbFirst => ((quintet & 0x1f).reverse_bits() >> 3) | (quintet & 0xe0),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+/// The letter case of encoded characters.
+///
+/// Z-base-32 is specified using lower-case letters, which is what the encoding
+/// functions produce by default. Upper-case output can be useful, for example, when
+/// generating QR codes since all upper-case z-base-32 characters are part of the QR
+/// code alphanumeric character set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Case {
+    /// Lower-case letters, such as "ybndrfg8".
+    #[default]
+    Lower,
+
+    /// Upper-case letters, such as "YBNDRFG8".
+    Upper,
+}
+
+impl Case {
+    pub(crate) fn convert_character(self, character: u8) -> u8 {
+        match self {
+            Case::Lower => character,
+            Case::Upper => character.to_ascii_uppercase(),
+        }
+    }
+
+    pub(crate) fn convert_characters(self, characters: &mut [u8]) {
+        if self == Case::Upper {
+            characters.make_ascii_uppercase();
+        }
+    }
+}
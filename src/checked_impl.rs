@@ -0,0 +1,374 @@
+#[cfg(feature = "std")]
+use crate::decode_impl::decode_uninit;
+use crate::decode_impl::{character_to_quintet, decode_slices};
+#[cfg(feature = "std")]
+use crate::encode_impl::encode;
+use crate::encode_impl::encode_slices;
+use crate::error::{
+    bits_overflow, input_buffer_doesnt_match_bits, invalid_checksum, invalid_format,
+    invalid_prefix, output_buffer_doesnt_match_bits,
+};
+use crate::tables::QUINTET_TO_CHARACTER;
+#[cfg(feature = "std")]
+use crate::util::required_octets_buffer_len;
+use crate::util::required_quintets_buffer_len;
+use crate::{UsageError, ZBase32Error};
+
+/// The number of checksum characters at the end of a checked value.
+pub const CHECKSUM_LEN: usize = 6;
+
+/// The maximum length of a human-readable prefix.
+pub const MAX_PREFIX_LEN: usize = 83;
+
+/// The character that separates the human-readable prefix from the encoded value.
+pub const SEPARATOR: u8 = b'1';
+
+const POLYMOD_GENERATORS: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+
+const BECH32M_CONSTANT: u32 = 0x2bc830a3;
+
+/// Running bech32m checksum over a sequence of quintets
+struct Polymod(u32);
+
+impl Polymod {
+    fn new(prefix: &[u8]) -> Polymod {
+        let mut polymod = Polymod(1);
+        for &character in prefix {
+            polymod.update(character.to_ascii_lowercase() >> 5);
+        }
+        polymod.update(0);
+        for &character in prefix {
+            polymod.update(character.to_ascii_lowercase() & 0x1f);
+        }
+        polymod
+    }
+
+    fn update(&mut self, quintet: u8) {
+        let top = self.0 >> 25;
+        self.0 = ((self.0 & 0x1ffffff) << 5) ^ quintet as u32;
+        for (index, generator) in POLYMOD_GENERATORS.iter().enumerate() {
+            if (top >> index) & 1 == 1 {
+                self.0 ^= generator;
+            }
+        }
+    }
+}
+
+fn check_prefix(prefix: &str) -> Result<(), UsageError> {
+    let is_valid = !prefix.is_empty()
+        && prefix.len() <= MAX_PREFIX_LEN
+        && prefix
+            .bytes()
+            .all(|x| (33..=126).contains(&x) && x != SEPARATOR);
+    if is_valid {
+        Ok(())
+    } else {
+        Err(invalid_prefix())
+    }
+}
+
+/// Calculate the number of characters that are required to hold a checked value
+/// of the specified number of bits, including the prefix, separator, and checksum.
+///
+/// This function will return an Err value if the specified number of bits
+/// would result in needing more than [`usize::MAX`] characters.
+pub fn required_checked_buffer_len(prefix: &str, bits: u64) -> Result<usize, UsageError> {
+    required_quintets_buffer_len(bits)?
+        .checked_add(prefix.len() + 1 + CHECKSUM_LEN)
+        .ok_or_else(bits_overflow)
+}
+
+/// Calculate the bech32m checksum of the encoded value `characters` under `prefix`.
+fn checksum(prefix: &str, characters: &[u8]) -> Result<u32, ZBase32Error> {
+    let mut polymod = Polymod::new(prefix.as_bytes());
+    for &character in characters {
+        polymod.update(character_to_quintet(character)?);
+    }
+    for _ in 0..CHECKSUM_LEN {
+        polymod.update(0);
+    }
+    Ok(polymod.0 ^ BECH32M_CONSTANT)
+}
+
+/// Produce the characters that represent `checksum`.
+fn checksum_characters(checksum: u32) -> impl Iterator<Item = u8> {
+    (0..CHECKSUM_LEN).map(move |index| {
+        let shift = 5 * (CHECKSUM_LEN - 1 - index);
+        QUINTET_TO_CHARACTER[(checksum >> shift) as usize & 0x1f]
+    })
+}
+
+/// Check the prefix, length, and checksum of a checked value, returning the
+/// characters of the encoded value.
+fn verify_checked<'a>(
+    prefix: &str,
+    in_characters: &'a [u8],
+    bits: u64,
+) -> Result<&'a [u8], ZBase32Error> {
+    check_prefix(prefix)?;
+    if in_characters.len() != required_checked_buffer_len(prefix, bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    let (in_prefix, rest) = in_characters.split_at(prefix.len());
+    if !in_prefix.eq_ignore_ascii_case(prefix.as_bytes()) || rest[0] != SEPARATOR {
+        return Err(invalid_format());
+    }
+
+    let mut polymod = Polymod::new(prefix.as_bytes());
+    for &character in &rest[1..] {
+        polymod.update(character_to_quintet(character)?);
+    }
+    if polymod.0 != BECH32M_CONSTANT {
+        return Err(invalid_checksum());
+    }
+
+    Ok(&rest[1..rest.len() - CHECKSUM_LEN])
+}
+
+/// Encode a buffer of octets (bytes) to a buffer of characters consisting of a
+/// human-readable prefix, the [`SEPARATOR`], the encoded value, and a checksum.
+///
+/// The prefix must be between 1 and [`MAX_PREFIX_LEN`] printable ASCII characters
+/// and must not contain the separator, since the separator is also a z-base-32
+/// character. The prefix is written in lower-case.
+///
+/// The checksum is the bech32m checksum computed over the prefix and the quintet
+/// values of the encoded value, written as [`CHECKSUM_LEN`] z-base-32 characters.
+/// As with bech32m, any 4 substituted characters are guaranteed to be detected as
+/// long as the entire value is no longer than 89 characters.
+///
+/// The length of `in_octets` must match the value returned by
+/// [`required_octets_buffer_len`](crate::low_level_decode::required_octets_buffer_len)
+/// and the length of `out_characters` must match the value returned by
+/// [`required_checked_buffer_len`].
+pub fn encode_checked_to_slice(
+    prefix: &str,
+    in_octets: &[u8],
+    out_characters: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    check_prefix(prefix)?;
+    if out_characters.len() != required_checked_buffer_len(prefix, bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+    let (out_prefix, rest) = out_characters.split_at_mut(prefix.len() + 1);
+    let value_len = rest.len() - CHECKSUM_LEN;
+    let (out_value, out_checksum) = rest.split_at_mut(value_len);
+    encode_slices(in_octets, out_value, bits)?;
+
+    for (out, character) in out_prefix.iter_mut().zip(prefix.bytes()) {
+        *out = character.to_ascii_lowercase();
+    }
+    out_prefix[prefix.len()] = SEPARATOR;
+
+    let checksum = checksum(prefix, out_value)?;
+    for (out, character) in out_checksum.iter_mut().zip(checksum_characters(checksum)) {
+        *out = character;
+    }
+    Ok(())
+}
+
+/// Verify and decode a buffer of characters produced by [`encode_checked_to_slice`]
+/// into a buffer of octets (bytes).
+///
+/// The prefix of `in_characters` must match `prefix`, ignoring case. An InputError
+/// is returned if it doesn't, or if the checksum is incorrect.
+///
+/// The length of `in_characters` must match the value returned by
+/// [`required_checked_buffer_len`] and the length of `out_octets` must match
+/// the value returned by
+/// [`required_octets_buffer_len`](crate::low_level_decode::required_octets_buffer_len).
+pub fn decode_checked_to_slice(
+    prefix: &str,
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    let value = verify_checked(prefix, in_characters, bits)?;
+    decode_slices(value, out_octets, bits)
+}
+
+/// Encode a slice of octets (bytes) to a [`String`] with a human-readable prefix
+/// and a checksum.
+///
+/// The output characters will be appended to `output`. See
+/// [`encode_checked_to_slice`] for a description of the format. If an error is
+/// returned, `output` is restored to its original length.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_checked(
+    prefix: &str,
+    input: &[u8],
+    output: &mut String,
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    check_prefix(prefix)?;
+    if input.len() != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    output.reserve(required_checked_buffer_len(prefix, bits)?);
+    let start = output.len();
+
+    output.extend(prefix.chars().map(|x| x.to_ascii_lowercase()));
+    output.push(SEPARATOR as char);
+    let value_start = output.len();
+    if let Err(err) = encode(input, output, bits) {
+        output.truncate(start);
+        return Err(err);
+    }
+    let checksum = checksum(prefix, &output.as_bytes()[value_start..])?;
+    output.extend(checksum_characters(checksum).map(char::from));
+    Ok(())
+}
+
+/// Verify and decode a value produced by [`encode_checked`] to a [`Vec`] of
+/// octets (bytes).
+///
+/// The output octets will be appended to `output`. See
+/// [`decode_checked_to_slice`] for details of the checks performed. If an error
+/// is returned, `output` is restored to its original length.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn decode_checked(
+    prefix: &str,
+    input: &str,
+    output: &mut Vec<u8>,
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    let value = verify_checked(prefix, input.as_bytes(), bits)?;
+    let needed_octets = required_octets_buffer_len(bits)?;
+    output.reserve(needed_octets);
+    let start = output.len();
+
+    // The checksum was verified above, so this can only fail if the value has
+    // non-zero trailing bits. The length of `output` is only changed once
+    // decoding has succeeded.
+    decode_uninit(
+        value,
+        &mut output.spare_capacity_mut()[..needed_octets],
+        bits,
+    )?;
+    // SAFETY: decode_uninit initialized the first `needed_octets` elements of
+    // the spare capacity.
+    unsafe { output.set_len(start + needed_octets) };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+
+    // The bech32m test vectors "a1lqfn3a" and
+    // "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx", with each quintet written
+    // using the z-base-32 alphabet instead of the bech32 alphabet.
+    const EMPTY_VECTOR: &str = "a19yjut7";
+    const VECTOR: &str = "abcdef1967h543azsiwu1toxqpcmkje8gfrdnbynptdrg";
+    const VECTOR_OCTETS: &[u8] = &[
+        255, 187, 205, 235, 56, 189, 171, 73, 202, 48, 123, 154, 197, 169, 40, 57, 138, 65, 136, 32,
+    ];
+
+    #[test]
+    fn test_bech32m_vectors() {
+        let mut encoded = String::new();
+        encode_checked("a", &[], &mut encoded, 0).unwrap();
+        assert_eq!(encoded, EMPTY_VECTOR);
+
+        let mut encoded = String::new();
+        encode_checked("abcdef", VECTOR_OCTETS, &mut encoded, 160).unwrap();
+        assert_eq!(encoded, VECTOR);
+
+        let mut decoded = Vec::new();
+        decode_checked("abcdef", VECTOR, &mut decoded, 160).unwrap();
+        assert_eq!(decoded, VECTOR_OCTETS);
+    }
+
+    #[test]
+    fn test_checked_round_trip() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut encoded = String::new();
+            encode_checked("inv", test.unencoded, &mut encoded, test.bits).unwrap();
+            assert!(encoded.starts_with("inv1"));
+
+            let mut decoded = Vec::new();
+            decode_checked("INV", &encoded.to_uppercase(), &mut decoded, test.bits).unwrap();
+            assert_eq!(decoded, test.unencoded);
+        }
+    }
+
+    #[test]
+    fn test_decode_checked_errors() {
+        let mut decoded = Vec::new();
+        // Wrong prefix
+        assert!(matches!(
+            decode_checked("abcdeg", VECTOR, &mut decoded, 160),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Each single substitution is detected
+        for index in 7..VECTOR.len() {
+            let mut corrupted = VECTOR.as_bytes().to_vec();
+            corrupted[index] = if corrupted[index] == b'y' { b'b' } else { b'y' };
+            let corrupted = String::from_utf8(corrupted).unwrap();
+            assert!(matches!(
+                decode_checked("abcdef", &corrupted, &mut decoded, 160),
+                Err(ZBase32Error::InputError(_))
+            ));
+        }
+        // Wrong length
+        assert!(matches!(
+            decode_checked("abcdef", VECTOR, &mut decoded, 155),
+            Err(ZBase32Error::UsageError(_))
+        ));
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_prefix() {
+        let mut encoded = String::new();
+        for prefix in ["", "a1", "a b", &"a".repeat(MAX_PREFIX_LEN + 1)].iter() {
+            assert!(matches!(
+                encode_checked(prefix, &[], &mut encoded, 0),
+                Err(ZBase32Error::UsageError(_))
+            ));
+        }
+        assert!(encoded.is_empty());
+    }
+
+    #[test]
+    fn test_checked_untrusted_bits() {
+        let mut encoded = String::new();
+        assert!(encode_checked("a", &[0], &mut encoded, 1 << 60).is_err());
+        assert!(encoded.is_empty());
+
+        let mut decoded = Vec::new();
+        assert!(decode_checked("a", EMPTY_VECTOR, &mut decoded, 1 << 60).is_err());
+        assert!(decoded.is_empty());
+    }
+
+    #[test]
+    fn test_checked_error_restores_output() {
+        let mut encoded = String::from("x");
+        // Non-zero trailing bits
+        assert!(encode_checked("a", &[0x01], &mut encoded, 7).is_err());
+        assert_eq!(encoded, "x");
+
+        let mut decoded = vec![1];
+        let mut corrupted = VECTOR.to_string();
+        corrupted.replace_range(10..11, "y");
+        assert!(decode_checked("abcdef", &corrupted, &mut decoded, 160).is_err());
+        assert_eq!(decoded, [1]);
+    }
+
+    #[test]
+    fn test_checked_to_slice() {
+        let mut encoded = vec![0u8; VECTOR.len()];
+        encode_checked_to_slice("ABCDEF", VECTOR_OCTETS, &mut encoded, 160).unwrap();
+        assert_eq!(encoded, VECTOR.as_bytes());
+
+        let mut decoded = vec![0u8; VECTOR_OCTETS.len()];
+        decode_checked_to_slice("abcdef", &encoded, &mut decoded, 160).unwrap();
+        assert_eq!(decoded, VECTOR_OCTETS);
+    }
+}
@@ -0,0 +1,342 @@
+use crate::decode_impl::{character_to_quintet, decode_slices};
+use crate::encode_impl::encode_slices;
+use crate::error::invalid_length;
+use crate::util::{octets_for_chars, required_quintets_buffer_len};
+use crate::ZBase32Error;
+use bytes::{Buf, BufMut, BytesMut};
+use std::fmt::{Debug, Display, Formatter};
+use std::io;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// A [`Decoder`] and [`Encoder`] for messages that are encoded as z-base-32 and
+/// separated by newlines.
+///
+/// Each message is a whole number of octets (bytes). When decoding, a `\r` before the
+/// `\n` is ignored and the final line doesn't need to end with a newline. Upper-case
+/// characters are accepted.
+///
+/// The maximum line length protects against peers that send very long lines. If a
+/// line exceeds it, [`CodecError::MaxLineLengthExceeded`] is returned and the rest
+/// of the line is discarded, so that decoding can continue with the next line.
+#[derive(Clone, Debug)]
+pub struct LineCodec {
+    max_length: usize,
+    next_index: usize,
+    lines: u64,
+    is_discarding: bool,
+}
+
+/// The error type of [`LineCodec`].
+pub enum CodecError {
+    /// The line with the specified 1-based line number was longer than the maximum
+    /// line length.
+    MaxLineLengthExceeded {
+        /// The 1-based line number of the line.
+        line: u64,
+    },
+
+    /// The line with the specified 1-based line number couldn't be decoded.
+    InvalidLine {
+        /// The 1-based line number of the line.
+        line: u64,
+        /// The 1-based column of the first character that isn't part of the
+        /// alphabet, or `None` if the line failed for another reason, such as
+        /// its length or non-zero trailing bits.
+        column: Option<usize>,
+        /// The reason that the line couldn't be decoded.
+        error: ZBase32Error,
+    },
+
+    /// An I/O error occurred.
+    Io(io::Error),
+}
+
+impl Debug for CodecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodecError::MaxLineLengthExceeded { line } => {
+                write!(f, "Line {} exceeds the maximum line length", line)
+            }
+            CodecError::InvalidLine {
+                line,
+                column: Some(column),
+                error,
+            } => write!(f, "Line {}, column {}: {}", line, column, error),
+            CodecError::InvalidLine {
+                line,
+                column: None,
+                error,
+            } => write!(f, "Line {}: {}", line, error),
+            CodecError::Io(err) => write!(f, "I/O Error: {}", err),
+        }
+    }
+}
+
+impl Display for CodecError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(self, f)
+    }
+}
+
+impl std::error::Error for CodecError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CodecError::MaxLineLengthExceeded { .. } => None,
+            CodecError::InvalidLine { error, .. } => Some(error),
+            CodecError::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for CodecError {
+    fn from(err: io::Error) -> Self {
+        CodecError::Io(err)
+    }
+}
+
+impl LineCodec {
+    /// Create a new `LineCodec` without a maximum line length.
+    ///
+    /// Since a peer could send an arbitrarily long line, [`LineCodec::new_with_max_length`]
+    /// should be used instead when the peer isn't trusted.
+    pub fn new() -> LineCodec {
+        LineCodec::new_with_max_length(usize::MAX)
+    }
+
+    /// Create a new `LineCodec` that rejects lines longer than `max_length`
+    /// characters, not including the newline or a `\r` before it.
+    pub fn new_with_max_length(max_length: usize) -> LineCodec {
+        LineCodec {
+            max_length,
+            next_index: 0,
+            lines: 0,
+            is_discarding: false,
+        }
+    }
+
+    /// Get the maximum line length.
+    pub fn max_length(&self) -> usize {
+        self.max_length
+    }
+
+    fn decode_line(&mut self, line: &[u8]) -> Result<Vec<u8>, CodecError> {
+        self.lines += 1;
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let mut output = Vec::new();
+        decode_whole_octets(line, &mut output).map_err(|error| CodecError::InvalidLine {
+            line: self.lines,
+            column: error_column(line),
+            error,
+        })?;
+        Ok(output)
+    }
+
+    fn exceeds_max_length(&self, line: &[u8]) -> bool {
+        line.strip_suffix(b"\r").unwrap_or(line).len() > self.max_length
+    }
+}
+
+fn error_column(line: &[u8]) -> Option<usize> {
+    line.iter()
+        .position(|&x| character_to_quintet(x).is_err())
+        .map(|index| index + 1)
+}
+
+fn decode_whole_octets(line: &[u8], output: &mut Vec<u8>) -> Result<(), ZBase32Error> {
+    let octets = octets_for_chars(line.len()).ok_or_else(invalid_length)?;
+    output.resize(octets, 0);
+    decode_slices(line, output, octets as u64 * 8)
+}
+
+impl Default for LineCodec {
+    fn default() -> Self {
+        LineCodec::new()
+    }
+}
+
+impl Decoder for LineCodec {
+    type Item = Vec<u8>;
+    type Error = CodecError;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Vec<u8>>, CodecError> {
+        loop {
+            // Look for a newline in the range that could still hold a line of
+            // up to `max_length` characters followed by a `\r`, without
+            // rescanning anything that was already checked by an earlier call.
+            let read_to = self.max_length.saturating_add(2).min(buf.len());
+            let newline_offset = buf[self.next_index..read_to]
+                .iter()
+                .position(|&x| x == b'\n');
+
+            match (self.is_discarding, newline_offset) {
+                (true, Some(offset)) => {
+                    // Found the end of the line being discarded - skip it and
+                    // continue with the next line.
+                    buf.advance(self.next_index + offset + 1);
+                    self.is_discarding = false;
+                    self.next_index = 0;
+                    self.lines += 1;
+                }
+                (true, None) => {
+                    buf.advance(read_to);
+                    self.next_index = 0;
+                    if buf.is_empty() {
+                        return Ok(None);
+                    }
+                }
+                (false, Some(offset)) => {
+                    let newline_index = self.next_index + offset;
+                    self.next_index = 0;
+                    let line = buf.split_to(newline_index + 1);
+                    if self.exceeds_max_length(&line[..newline_index]) {
+                        self.lines += 1;
+                        return Err(CodecError::MaxLineLengthExceeded { line: self.lines });
+                    }
+                    return self.decode_line(&line[..newline_index]).map(Some);
+                }
+                (false, None) if self.exceeds_max_length(&buf[..read_to]) => {
+                    self.is_discarding = true;
+                    return Err(CodecError::MaxLineLengthExceeded {
+                        line: self.lines + 1,
+                    });
+                }
+                (false, None) => {
+                    self.next_index = read_to;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Vec<u8>>, CodecError> {
+        Ok(match self.decode(buf)? {
+            Some(message) => Some(message),
+            None if buf.is_empty() || self.is_discarding => None,
+            None => {
+                self.next_index = 0;
+                let line = buf.split();
+                Some(self.decode_line(&line)?)
+            }
+        })
+    }
+}
+
+impl<T: AsRef<[u8]>> Encoder<T> for LineCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), CodecError> {
+        let item = item.as_ref();
+        let bits = item.len() as u64 * 8;
+        let characters =
+            required_quintets_buffer_len(bits).expect("A slice always fits in usize characters");
+        let start = dst.len();
+        dst.resize(start + characters, 0);
+        encode_slices(item, &mut dst[start..], bits).expect("Encoding whole octets can't fail");
+        dst.put_u8(b'\n');
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+
+    #[test]
+    fn test_encode() {
+        let mut codec = LineCodec::new();
+        let mut buf = BytesMut::new();
+        codec.encode(&[0xf0, 0xbf, 0xc7][..], &mut buf).unwrap();
+        codec.encode(Vec::new(), &mut buf).unwrap();
+        codec.encode(b"\x00", &mut buf).unwrap();
+        assert_eq!(&buf[..], b"6n9hq\n\nyy\n");
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut codec = LineCodec::new_with_max_length(1024);
+        let mut buf = BytesMut::new();
+        let messages: Vec<&[u8]> = STANDARD_TEST_DATA
+            .iter()
+            .chain(RANDOM_TEST_DATA)
+            .filter(|test| test.unencoded.len() as u64 * 8 == test.bits)
+            .map(|test| test.unencoded)
+            .collect();
+        for message in messages.iter() {
+            codec.encode(message, &mut buf).unwrap();
+        }
+        for message in messages.iter() {
+            assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), *message);
+        }
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_decode_partial_lines() {
+        let mut codec = LineCodec::new();
+        let mut buf = BytesMut::from(&b"6n9"[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(b"hq\r\nYY");
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), [0xf0, 0xbf, 0xc7]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        assert_eq!(codec.decode_eof(&mut buf).unwrap().unwrap(), [0]);
+        assert!(codec.decode_eof(&mut buf).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut codec = LineCodec::new();
+        let mut buf = BytesMut::from(&b"yy\nyyy\nyb\ny!\n\xc3\xa9\nyy\n"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), [0]);
+        let expected = [(2, None), (3, None), (4, Some(2)), (5, Some(1))];
+        for (expected_line, expected_column) in expected.iter() {
+            match codec.decode(&mut buf) {
+                Err(CodecError::InvalidLine { line, column, .. }) => {
+                    assert_eq!((line, column), (*expected_line, *expected_column))
+                }
+                _ => panic!("Expected an error for line {}", expected_line),
+            }
+        }
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), [0]);
+
+        let err = codec.decode(&mut BytesMut::from(&b"y!\n"[..])).unwrap_err();
+        assert!(err.to_string().starts_with("Line 7, column 2: "));
+        let err = codec.decode(&mut BytesMut::from(&b"yb\n"[..])).unwrap_err();
+        assert!(err.to_string().starts_with("Line 8: "));
+    }
+
+    #[test]
+    fn test_max_length() {
+        let mut codec = LineCodec::new_with_max_length(4);
+        let mut buf = BytesMut::from(&b"yyyy\nyyyyyyy"[..]);
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), [0, 0]);
+        match codec.decode(&mut buf) {
+            Err(CodecError::MaxLineLengthExceeded { line }) => assert_eq!(line, 2),
+            _ => panic!("Expected the maximum line length to be exceeded"),
+        }
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(b"yyy\nyy\n");
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), [0]);
+        match codec.decode(&mut BytesMut::from(&b"y!\n"[..])) {
+            Err(CodecError::InvalidLine { line, .. }) => assert_eq!(line, 4),
+            _ => panic!("Expected an error for line 4"),
+        }
+    }
+
+    #[test]
+    fn test_max_length_carriage_return() {
+        let mut codec = LineCodec::new_with_max_length(4);
+        let mut buf = BytesMut::from(&b"yyyy\r"[..]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+        buf.extend_from_slice(b"\nyyyyy\r\nyyyyy\nyy\r\n");
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), [0, 0]);
+        for expected_line in 2..=3 {
+            match codec.decode(&mut buf) {
+                Err(CodecError::MaxLineLengthExceeded { line }) => assert_eq!(line, expected_line),
+                _ => panic!("Expected the maximum line length to be exceeded"),
+            }
+        }
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap(), [0]);
+        assert!(codec.decode(&mut buf).unwrap().is_none());
+    }
+}
@@ -0,0 +1,320 @@
+use crate::error::{
+    input_buffer_doesnt_match_bits, invalid_character, output_buffer_doesnt_match_bits,
+    trailing_nonzero_bits,
+};
+use crate::tables::QUINTET_TO_CHARACTER;
+use crate::util::{read_octet_at_bit, required_octets_buffer_len, required_quintets_buffer_len};
+use crate::ZBase32Error;
+use core::hint::black_box;
+
+// All of the helpers below work with masks which are either all ones (true) or
+// all zeros (false) so that values can be selected without branching. The
+// masks are passed through `black_box` to discourage the compiler from turning
+// the selection back into a branch.
+
+/// Produce a mask that is all ones if `a == b`.
+fn mask_eq(a: u8, b: u8) -> u32 {
+    let diff = (a ^ b) as u32;
+    black_box((diff.wrapping_sub(1) >> 31).wrapping_neg())
+}
+
+/// Produce a mask that is all ones if `low <= x <= high`.
+fn mask_in_range(x: u8, low: u8, high: u8) -> u32 {
+    let above_low = (x as u32).wrapping_sub(low as u32) >> 31;
+    let below_high = (high as u32).wrapping_sub(x as u32) >> 31;
+    black_box(((above_low | below_high) ^ 1).wrapping_neg())
+}
+
+/// Produce a mask that is all ones if `x` isn't zero.
+fn mask_nonzero(x: u8) -> u32 {
+    !mask_eq(x, 0)
+}
+
+/// Lower-case `character` if it is an upper-case ASCII letter.
+fn fold_case(character: u8) -> u8 {
+    character | (mask_in_range(character, b'A', b'Z') & 0x20) as u8
+}
+
+/// Convert a quintet to its character, visiting every entry of the alphabet
+/// so that the memory access pattern doesn't depend on the value.
+fn quintet_to_character_ct(quintet: u8) -> u8 {
+    let mut character = 0u32;
+    for (value, &alphabet_character) in QUINTET_TO_CHARACTER.iter().enumerate() {
+        character |= mask_eq(quintet, value as u8) & alphabet_character as u32;
+    }
+    character as u8
+}
+
+/// Convert a character to its quintet, visiting every entry of the alphabet
+/// so that the memory access pattern doesn't depend on the value. The returned
+/// mask is all ones if the character isn't part of the alphabet, in which case
+/// the quintet is 0.
+fn character_to_quintet_ct(character: u8) -> (u8, u32) {
+    let character = fold_case(character);
+    let mut quintet = 0u32;
+    let mut found = 0u32;
+    for (value, &alphabet_character) in QUINTET_TO_CHARACTER.iter().enumerate() {
+        let matches = mask_eq(character, alphabet_character);
+        quintet |= matches & value as u32;
+        found |= matches;
+    }
+    (quintet as u8, !found)
+}
+
+/// Encode a buffer of octets (bytes) to a buffer of characters without
+/// secret-dependent branches or table lookups.
+///
+/// This method produces the same output as
+/// [`encode_slices`](crate::low_level_encode::encode_slices), but the time
+/// it takes and the memory it accesses only depend on `bits`, so it is
+/// suitable for encoding secrets such as private keys. If the input has non-zero
+/// trailing bits, this is only detected once the whole input has been processed,
+/// and `out_characters` is filled with zeros before the error is returned.
+///
+/// The length of `in_octets` must match the value returned by
+/// [`required_octets_buffer_len`] and the length of `out_characters`
+/// must match the value returned by [`required_quintets_buffer_len`].
+pub fn encode_slices_ct(
+    in_octets: &[u8],
+    out_characters: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    if in_octets.len() != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    if out_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+
+    for (index, out_character) in out_characters.iter_mut().enumerate() {
+        let quintet = read_octet_at_bit(in_octets, index as u64 * 5) >> 3;
+        *out_character = quintet_to_character_ct(quintet);
+    }
+
+    // Any bits of the last octet past `bits` must be zero
+    let trailing_bits = match (in_octets.last(), bits % 8) {
+        (Some(&last_octet), used_bits) if used_bits != 0 => last_octet & (0xff >> used_bits),
+        _ => 0,
+    };
+    if mask_nonzero(trailing_bits) != 0 {
+        out_characters.iter_mut().for_each(|x| *x = 0);
+        return Err(trailing_nonzero_bits());
+    }
+
+    Ok(())
+}
+
+/// Decode a buffer of characters to a buffer of octets (bytes) without
+/// secret-dependent branches or table lookups.
+///
+/// This method accepts the same input as
+/// [`decode_slices`](crate::low_level_decode::decode_slices), but the time
+/// it takes and the memory it accesses only depend on `bits`, so it is
+/// suitable for decoding secrets such as private keys. Errors in the input are
+/// only detected once the whole input has been processed and don't indicate
+/// which character was invalid. If an error is returned, `out_octets` is
+/// filled with zeros.
+///
+/// The length of `in_characters` must match the value returned by
+/// [`required_quintets_buffer_len`] and the length of `out_octets`
+/// must match the value returned by [`required_octets_buffer_len`].
+pub fn decode_slices_ct(
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    if in_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    if out_octets.len() != required_octets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+
+    let mut invalid_characters = 0u32;
+    for &character in in_characters {
+        invalid_characters |= character_to_quintet_ct(character).1;
+    }
+
+    // Any bits of the last quintet past `bits` must be zero
+    let trailing_bits = match in_characters.last() {
+        Some(&last_character) => {
+            let (last_quintet, _) = character_to_quintet_ct(last_character);
+            let last_quintet_bits = (bits - (in_characters.len() as u64 - 1) * 5) as u32;
+            last_quintet & (0x1f >> last_quintet_bits)
+        }
+        None => 0,
+    };
+    let trailing_nonzero = mask_nonzero(trailing_bits);
+
+    let quintet_at = |index: usize| match in_characters.get(index) {
+        Some(&character) => character_to_quintet_ct(character).0 as u32,
+        None => 0,
+    };
+    for (index, out_octet) in out_octets.iter_mut().enumerate() {
+        let bit = index * 8;
+        let first = bit / 5;
+        let window = quintet_at(first) << 10 | quintet_at(first + 1) << 5 | quintet_at(first + 2);
+        *out_octet = (window >> (7 - bit % 5)) as u8;
+    }
+
+    if (invalid_characters | trailing_nonzero) != 0 {
+        out_octets.iter_mut().for_each(|x| *x = 0);
+        return Err(if invalid_characters != 0 {
+            invalid_character()
+        } else {
+            trailing_nonzero_bits()
+        });
+    }
+
+    Ok(())
+}
+
+/// Compare two encoded values without secret-dependent branches.
+///
+/// The comparison ignores the case of letters, so a value that was typed in
+/// upper-case compares equal to the lower-case value that was issued. The
+/// values aren't validated. The time taken only depends on the lengths of
+/// the values, which are assumed not to be secret.
+pub fn eq_ct(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut difference = 0u8;
+    for (&x, &y) in a.iter().zip(b) {
+        difference |= fold_case(x) ^ fold_case(y);
+    }
+    mask_nonzero(difference) == 0
+}
+
+/// Encode a slice of octets (bytes) to a [`String`] without secret-dependent
+/// branches or table lookups.
+///
+/// This method is the high-level equivalent of [`encode_slices_ct`]. The output
+/// characters are written directly into `output`, without an intermediate buffer
+/// that would be left behind holding them, and `output` is left unmodified if an
+/// error is returned.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_ct(input: &[u8], output: &mut String, bits: u64) -> Result<(), ZBase32Error> {
+    if input.len() != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    // SAFETY: the Vec is only extended with zeros and ASCII characters, and is
+    // truncated to its original length if encoding fails, so the String always
+    // holds valid UTF-8.
+    let output = unsafe { output.as_mut_vec() };
+    let start = output.len();
+    output.resize(start + required_quintets_buffer_len(bits)?, 0);
+    let result = encode_slices_ct(input, &mut output[start..], bits);
+    if result.is_err() {
+        output.truncate(start);
+    }
+    result
+}
+
+/// Decode a slice of characters to a [`Vec`] of octets (bytes) without
+/// secret-dependent branches or table lookups.
+///
+/// This method is the high-level equivalent of [`decode_slices_ct`]. The output
+/// octets are appended to `output`, which is left unmodified if an error is
+/// returned.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn decode_ct(input: &str, output: &mut Vec<u8>, bits: u64) -> Result<(), ZBase32Error> {
+    if input.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    let start = output.len();
+    output.resize(start + required_octets_buffer_len(bits)?, 0);
+    let result = decode_slices_ct(input.as_bytes(), &mut output[start..], bits);
+    if result.is_err() {
+        output.truncate(start);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_impl::character_to_quintet;
+    use crate::test_data::{RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+
+    #[test]
+    fn test_character_mapping() {
+        for character in 0..=255u8 {
+            let (quintet, invalid) = character_to_quintet_ct(character);
+            match character_to_quintet(character) {
+                Ok(expected) => {
+                    assert_eq!(invalid, 0);
+                    assert_eq!(quintet, expected);
+                    assert_eq!(quintet_to_character_ct(quintet), fold_case(character));
+                }
+                Err(_) => assert_eq!((quintet, invalid), (0, !0)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_slices_ct() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut encoded = vec![0; test.encoded.len()];
+            encode_slices_ct(test.unencoded, &mut encoded, test.bits).unwrap();
+            assert_eq!(encoded, test.encoded.as_bytes());
+
+            let mut decoded = vec![0; test.unencoded.len()];
+            decode_slices_ct(test.encoded.as_bytes(), &mut decoded, test.bits).unwrap();
+            assert_eq!(decoded, test.unencoded);
+
+            let upper = test.encoded.to_ascii_uppercase();
+            decode_slices_ct(upper.as_bytes(), &mut decoded, test.bits).unwrap();
+            assert_eq!(decoded, test.unencoded);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut characters = [0xffu8; 2];
+        assert!(encode_slices_ct(&[0x01], &mut characters, 7).is_err());
+        assert_eq!(characters, [0, 0]);
+        assert!(encode_slices_ct(&[0x01, 0x00], &mut characters, 8).is_err());
+
+        let mut octets = [0xffu8; 1];
+        assert!(decode_slices_ct(b"yb", &mut octets, 8).is_err());
+        assert_eq!(octets, [0]);
+        assert!(decode_slices_ct(b"!y", &mut octets, 8).is_err());
+        assert!(decode_slices_ct(b"y\x11", &mut octets, 8).is_err());
+        assert!(decode_slices_ct(b"yyy", &mut octets, 8).is_err());
+    }
+
+    #[test]
+    fn test_eq_ct() {
+        assert!(eq_ct(b"", b""));
+        assert!(eq_ct(b"6n9hq", b"6n9hq"));
+        assert!(eq_ct(b"6n9hq", b"6N9HQ"));
+        assert!(!eq_ct(b"6n9hq", b"6n9hr"));
+        assert!(!eq_ct(b"6n9hq", b"6n9h"));
+        assert!(!eq_ct(b"1", b"\x11"));
+    }
+
+    #[test]
+    fn test_high_level_ct() {
+        let mut encoded = String::from("x");
+        encode_ct(&[0xf0, 0xbf, 0xc7], &mut encoded, 24).unwrap();
+        assert_eq!(encoded, "x6n9hq");
+        assert!(encode_ct(&[0x01], &mut encoded, 7).is_err());
+        assert_eq!(encoded, "x6n9hq");
+
+        let mut decoded = vec![1];
+        decode_ct("6n9hq", &mut decoded, 24).unwrap();
+        assert_eq!(decoded, [1, 0xf0, 0xbf, 0xc7]);
+        assert!(decode_ct("6n9h!", &mut decoded, 24).is_err());
+        assert_eq!(decoded, [1, 0xf0, 0xbf, 0xc7]);
+
+        // A huge number of bits must be rejected before anything is allocated
+        assert!(encode_ct(&[0], &mut encoded, 1 << 60).is_err());
+        assert!(decode_ct("yy", &mut decoded, 1 << 60).is_err());
+        assert_eq!(decoded, [1, 0xf0, 0xbf, 0xc7]);
+    }
+}
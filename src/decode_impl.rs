@@ -1,25 +1,40 @@
+use crate::bit_order::BitOrder;
 use crate::error::{
-    input_buffer_doesnt_match_bits, invalid_character, output_buffer_doesnt_match_bits,
+    input_buffer_doesnt_match_bits, invalid_character, non_canonical_case,
+    output_buffer_doesnt_match_bits, trailing_nonzero_bits,
 };
 use crate::stateful_decoder::{
     quintet_has_valid_trailing_bits, HaveOctets, NeedQuintets, NextOctetResult,
     ProvideQuintetResult,
 };
 use crate::tables::{CHARACTER_MIN_VALUE, CHARACTER_TO_QUINTET};
-use crate::util::{required_octets_buffer_len, required_quintets_buffer_len};
+use crate::util::{
+    check_bit_range, fill_uninit, possible_bits_for_chars, required_octets_buffer_len,
+    required_quintets_buffer_len, write_octet_at_bit,
+};
+#[cfg(feature = "rayon")]
+use crate::util::{OCTETS_PER_GROUP, PAR_CHUNK_GROUPS, QUINTETS_PER_GROUP};
 use crate::ZBase32Error;
-use core::iter::Peekable;
+use core::cell::Cell;
+use core::mem::MaybeUninit;
+use core::ops::RangeInclusive;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(all(feature = "std", feature = "zeroize"))]
+use zeroize::Zeroizing;
 
 enum QuintetsToOctetsIterState {
     Initial(NeedQuintets),
     HaveOctets(HaveOctets),
+    Failed(ZBase32Error),
 }
 
 struct QuintetsToOctetsIter<I>
 where
     I: Iterator<Item = Result<u8, ZBase32Error>>,
 {
-    quintet_iter: Peekable<I>,
+    quintet_iter: I,
+    remaining_quintets: usize,
     state: Option<QuintetsToOctetsIterState>,
 }
 
@@ -27,24 +42,56 @@ impl<I> QuintetsToOctetsIter<I>
 where
     I: Iterator<Item = Result<u8, ZBase32Error>>,
 {
-    fn new(quintet_iter: I, need_quintets: NeedQuintets) -> QuintetsToOctetsIter<I> {
+    // The iterator produces an error if `quintet_iter` doesn't produce exactly
+    // the number of quintets required for `bits`.
+    fn new(mut quintet_iter: I, bits: u64) -> QuintetsToOctetsIter<I> {
+        let (remaining_quintets, state) = match (
+            required_quintets_buffer_len(bits),
+            calc_last_quintet_bits(bits),
+        ) {
+            (Ok(quintets), Some(last_quintet_bits)) => (
+                quintets,
+                Some(QuintetsToOctetsIterState::Initial(NeedQuintets::new(
+                    last_quintet_bits,
+                ))),
+            ),
+            (Ok(_), None) => match quintet_iter.next() {
+                Some(_) => (
+                    0,
+                    Some(QuintetsToOctetsIterState::Failed(
+                        input_buffer_doesnt_match_bits().into(),
+                    )),
+                ),
+                None => (0, None),
+            },
+            (Err(err), _) => (0, Some(QuintetsToOctetsIterState::Failed(err.into()))),
+        };
         QuintetsToOctetsIter {
-            quintet_iter: quintet_iter.peekable(),
-            state: Some(QuintetsToOctetsIterState::Initial(need_quintets)),
+            quintet_iter,
+            remaining_quintets,
+            state,
         }
     }
 }
 
 fn refill<I>(
-    quintet_iter: &mut Peekable<I>,
+    quintet_iter: &mut I,
+    remaining_quintets: &mut usize,
     mut need_quintets: NeedQuintets,
 ) -> Result<Option<QuintetsToOctetsIterState>, ZBase32Error>
 where
     I: Iterator<Item = Result<u8, ZBase32Error>>,
 {
     loop {
-        let quintet = quintet_iter.next().unwrap()?;
-        let last_quintet = quintet_iter.peek().is_none();
+        let quintet = match quintet_iter.next() {
+            Some(quintet) => quintet?,
+            None => return Err(input_buffer_doesnt_match_bits().into()),
+        };
+        *remaining_quintets -= 1;
+        let last_quintet = *remaining_quintets == 0;
+        if last_quintet && quintet_iter.next().is_some() {
+            return Err(input_buffer_doesnt_match_bits().into());
+        }
         match need_quintets.provide_quintet(quintet, last_quintet)? {
             ProvideQuintetResult::NeedQuintets(need_more) => need_quintets = need_more,
             ProvideQuintetResult::HaveOctets(have_octets) => {
@@ -72,14 +119,13 @@ where
                 // After doing this, we'll loop and never end up in this state
                 // again.
                 QuintetsToOctetsIterState::Initial(need_quintets) => {
-                    if self.quintet_iter.peek().is_some() {
-                        match refill(&mut self.quintet_iter, need_quintets) {
-                            Ok(new_state) => self.state = new_state,
-                            Err(err) => return Some(Err(err)),
-                        }
-                    } else {
-                        // Do nothing - this means that the input iterator was empty -
-                        // which shouldn't actually be possible.
+                    match refill(
+                        &mut self.quintet_iter,
+                        &mut self.remaining_quintets,
+                        need_quintets,
+                    ) {
+                        Ok(new_state) => self.state = new_state,
+                        Err(err) => return Some(Err(err)),
                     }
                 }
 
@@ -95,7 +141,11 @@ where
                             return Some(Ok(octet));
                         }
                         NextOctetResult::NeedQuintets(need_quintets) => {
-                            match refill(&mut self.quintet_iter, need_quintets) {
+                            match refill(
+                                &mut self.quintet_iter,
+                                &mut self.remaining_quintets,
+                                need_quintets,
+                            ) {
                                 Ok(new_state) => self.state = new_state,
                                 Err(err) => return Some(Err(err)),
                             }
@@ -103,6 +153,10 @@ where
                         NextOctetResult::Complete => {}
                     }
                 }
+
+                // If the iterator couldn't be started, we return the error once
+                // and then complete.
+                QuintetsToOctetsIterState::Failed(err) => return Some(Err(err)),
             }
         }
     }
@@ -110,7 +164,7 @@ where
 
 /// Convert a character code value (such as "y") to its integer
 /// value (such as 0).
-pub fn character_to_quintet(character: u8) -> Result<u8, ZBase32Error> {
+pub const fn character_to_quintet(character: u8) -> Result<u8, ZBase32Error> {
     if character < CHARACTER_MIN_VALUE {
         return Err(invalid_character());
     } else if (character - CHARACTER_MIN_VALUE) as usize >= CHARACTER_TO_QUINTET.len() {
@@ -123,6 +177,20 @@ pub fn character_to_quintet(character: u8) -> Result<u8, ZBase32Error> {
     Ok(val)
 }
 
+/// Convert a character code value (such as "y") to its integer
+/// value (such as 0), only accepting the lower-case characters produced
+/// by the encoding functions.
+///
+/// Unlike [`character_to_quintet`], upper-case characters are rejected with an
+/// error that indicates that the character has a non-canonical case.
+pub fn character_to_quintet_strict(character: u8) -> Result<u8, ZBase32Error> {
+    let quintet = character_to_quintet(character)?;
+    if character.is_ascii_uppercase() {
+        return Err(non_canonical_case());
+    }
+    Ok(quintet)
+}
+
 fn calc_last_quintet_bits(bits: u64) -> Option<u8> {
     if bits == 0 {
         None
@@ -136,13 +204,46 @@ fn calc_last_quintet_bits(bits: u64) -> Option<u8> {
 
 /// Determine if the last quintet is, given the number of bits to decode
 pub fn is_last_quintet_valid(bits: u64, quintet: u8) -> bool {
+    is_last_quintet_valid_with_bit_order(bits, quintet, BitOrder::MsbFirst)
+}
+
+/// Determine if the last quintet is valid, given the number of bits to decode
+/// and the [`BitOrder`] of the quintet.
+pub fn is_last_quintet_valid_with_bit_order(bits: u64, quintet: u8, bit_order: BitOrder) -> bool {
     if let Some(last_quintet_bits) = calc_last_quintet_bits(bits) {
-        quintet <= 31 && quintet_has_valid_trailing_bits(last_quintet_bits, quintet)
+        quintet <= 31
+            && quintet_has_valid_trailing_bits(
+                last_quintet_bits,
+                bit_order.convert_quintet(quintet),
+            )
     } else {
         false
     }
 }
 
+/// Calculate the range of bit counts that a buffer of characters could be a
+/// valid encoding of.
+///
+/// This narrows the range returned by [`possible_bits_for_chars`] using the
+/// trailing zero bits of the last character: since trailing bits must be zero,
+/// a last character with no trailing zero bits means that all 5 of its bits are
+/// in use. Only the last character is checked - it may still be necessary to
+/// validate the rest of the buffer.
+///
+/// This function returns an Err value if the last character isn't valid, or if
+/// the buffer is too long for its number of bits to fit in a [`u64`].
+pub fn possible_bits_for_input(in_characters: &[u8]) -> Result<RangeInclusive<u64>, ZBase32Error> {
+    let range = possible_bits_for_chars(in_characters.len())?;
+    match in_characters.last() {
+        Some(&character) => {
+            let trailing_zeros = (character_to_quintet(character)? | 0x20).trailing_zeros() as u64;
+            let min_bits = *range.end() - 4;
+            Ok(min_bits.max(*range.end() - trailing_zeros)..=*range.end())
+        }
+        None => Ok(range),
+    }
+}
+
 /// Convert a buffer of quintet integer values (ie, integers of the
 /// range 0-31) into a buffer of octet (byte) values.
 ///
@@ -153,6 +254,21 @@ pub fn quintets_to_octets(
     in_quintets: &[u8],
     out_octets: &mut [u8],
     bits: u64,
+) -> Result<(), ZBase32Error> {
+    quintets_to_octets_with_bit_order(in_quintets, out_octets, bits, BitOrder::MsbFirst)
+}
+
+/// Convert a buffer of quintet integer values (ie, integers of the
+/// range 0-31) into a buffer of octet (byte) values using the specified
+/// [`BitOrder`].
+///
+/// This method is the same as [`quintets_to_octets`] except that both the
+/// quintets and the octets use the bit order `bit_order`.
+pub fn quintets_to_octets_with_bit_order(
+    in_quintets: &[u8],
+    out_octets: &mut [u8],
+    bits: u64,
+    bit_order: BitOrder,
 ) -> Result<(), ZBase32Error> {
     if in_quintets.len() != required_quintets_buffer_len(bits)? {
         return Err(input_buffer_doesnt_match_bits().into());
@@ -160,19 +276,15 @@ pub fn quintets_to_octets(
     if out_octets.len() != required_octets_buffer_len(bits)? {
         return Err(output_buffer_doesnt_match_bits().into());
     }
-    let last_quintet_bits = if let Some(x) = calc_last_quintet_bits(bits) {
-        x
-    } else {
-        return Ok(());
-    };
-
     let octet_iter = QuintetsToOctetsIter::new(
-        in_quintets.iter().map(|&x| Ok(x)),
-        NeedQuintets::new(last_quintet_bits),
+        in_quintets
+            .iter()
+            .map(|&x| Ok(bit_order.convert_quintet(x))),
+        bits,
     );
 
     for (out, next_octet) in out_octets.iter_mut().zip(octet_iter) {
-        *out = next_octet?;
+        *out = bit_order.convert_octet(next_octet?);
     }
 
     Ok(())
@@ -193,76 +305,468 @@ pub fn quintets_to_octets(
 /// The length of `in_characters` must match the value returned by
 /// [`required_quintets_buffer_len`] and the length of `out_octets`
 /// must match the value returned by [`required_octets_buffer_len`].
+///
+/// If the input is rejected, the whole of `out_octets` is filled with zeros,
+/// including any octets that hadn't been written yet, so that partial output is
+/// never left behind. Usage errors, such as buffer lengths that don't match
+/// `bits`, are returned before anything is written.
 pub fn decode_slices(
     in_characters: &[u8],
     out_octets: &mut [u8],
     bits: u64,
 ) -> Result<(), ZBase32Error> {
+    decode_slices_with_bit_order(in_characters, out_octets, bits, BitOrder::MsbFirst)
+}
+
+/// Decode a buffer of characters to a buffer of octets (bytes) using the
+/// specified [`BitOrder`].
+///
+/// This method is the same as [`decode_slices`] except that the bits of
+/// each quintet and each octet are taken in the order `bit_order`.
+pub fn decode_slices_with_bit_order(
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    bits: u64,
+    bit_order: BitOrder,
+) -> Result<(), ZBase32Error> {
+    decode_slices_using(
+        in_characters,
+        out_octets,
+        bits,
+        bit_order,
+        character_to_quintet,
+    )
+}
+
+/// Decode a buffer of characters to a buffer of octets (bytes), only accepting
+/// the exact lower-case output of the encoding functions.
+///
+/// This method is the same as [`decode_slices`] except that upper-case characters
+/// are rejected using [`character_to_quintet_strict`]. This is useful when the
+/// encoded value must have a single textual form, for example, when it is used as
+/// a key or is covered by a signature.
+pub fn decode_slices_strict(
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    decode_slices_using(
+        in_characters,
+        out_octets,
+        bits,
+        BitOrder::MsbFirst,
+        character_to_quintet_strict,
+    )
+}
+
+fn decode_slices_using<F>(
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    bits: u64,
+    bit_order: BitOrder,
+    character_to_quintet: F,
+) -> Result<(), ZBase32Error>
+where
+    F: Fn(u8) -> Result<u8, ZBase32Error>,
+{
     if in_characters.len() != required_quintets_buffer_len(bits)? {
         return Err(input_buffer_doesnt_match_bits().into());
     }
     if out_octets.len() != required_octets_buffer_len(bits)? {
         return Err(output_buffer_doesnt_match_bits().into());
     }
-    let last_quintet_bits = if let Some(x) = calc_last_quintet_bits(bits) {
-        x
-    } else {
-        return Ok(());
-    };
-
     let octet_iter = QuintetsToOctetsIter::new(
-        in_characters.iter().map(|&x| character_to_quintet(x)),
-        NeedQuintets::new(last_quintet_bits),
+        in_characters
+            .iter()
+            .map(|&x| character_to_quintet(x).map(|x| bit_order.convert_quintet(x))),
+        bits,
     );
 
+    let mut result = Ok(());
     for (out, next_octet) in out_octets.iter_mut().zip(octet_iter) {
-        *out = next_octet?;
+        match next_octet {
+            Ok(octet) => *out = bit_order.convert_octet(octet),
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+    if result.is_err() {
+        out_octets.fill(0);
+    }
+
+    result
+}
+
+/// Decode a buffer of characters to a possibly uninitialized buffer of octets (bytes).
+///
+/// This method is the same as [`decode_slices`] except that `out_octets` doesn't
+/// need to be initialized, which avoids zero-filling a buffer that is about to be
+/// overwritten. On success, `out_octets` is returned as an initialized slice.
+///
+/// The length of `in_characters` must match the value returned by
+/// [`required_quintets_buffer_len`] and the length of `out_octets`
+/// must match the value returned by [`required_octets_buffer_len`].
+pub fn decode_uninit<'a>(
+    in_characters: &[u8],
+    out_octets: &'a mut [MaybeUninit<u8>],
+    bits: u64,
+) -> Result<&'a mut [u8], ZBase32Error> {
+    decode_uninit_using(in_characters, out_octets, bits, character_to_quintet)
+}
+
+fn decode_uninit_using<'a, F>(
+    in_characters: &[u8],
+    out_octets: &'a mut [MaybeUninit<u8>],
+    bits: u64,
+    character_to_quintet: F,
+) -> Result<&'a mut [u8], ZBase32Error>
+where
+    F: Fn(u8) -> Result<u8, ZBase32Error>,
+{
+    if in_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    if out_octets.len() != required_octets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+    let octet_iter =
+        QuintetsToOctetsIter::new(in_characters.iter().map(|&x| character_to_quintet(x)), bits);
+    fill_uninit(out_octets, octet_iter)
+}
+
+/// Check that a buffer of characters is a valid encoding of `bits` bits, without
+/// decoding it.
+///
+/// This performs the same checks as [`decode_slices`] - the length of `in_characters`,
+/// that every character is valid, and that the trailing bits of the final character
+/// are zero - but doesn't require an output buffer.
+///
+/// The length of `in_characters` must match the value returned by
+/// [`required_quintets_buffer_len`].
+pub fn validate(in_characters: &[u8], bits: u64) -> Result<(), ZBase32Error> {
+    validate_using(in_characters, bits, character_to_quintet)
+}
+
+/// Check that a buffer of characters is exactly the lower-case encoding of `bits`
+/// bits, without decoding it.
+///
+/// This performs the same checks as [`decode_slices_strict`] but doesn't require
+/// an output buffer.
+pub fn validate_strict(in_characters: &[u8], bits: u64) -> Result<(), ZBase32Error> {
+    validate_using(in_characters, bits, character_to_quintet_strict)
+}
+
+/// Determine if a buffer of characters is a valid encoding of `bits` bits.
+///
+/// This is the same as [`validate`], except that it returns `false` instead of
+/// an Err value.
+pub fn is_valid(in_characters: &[u8], bits: u64) -> bool {
+    validate(in_characters, bits).is_ok()
+}
+
+/// Determine if `input` is exactly the lower-case encoding of a `bits` bit value
+/// as produced by the encoding functions.
+///
+/// This returns `false` if `input` has the wrong length for `bits`, contains
+/// invalid or upper-case characters, or has non-zero trailing bits.
+pub fn is_canonical(input: &[u8], bits: u64) -> bool {
+    validate_strict(input, bits).is_ok()
+}
+
+fn validate_using<F>(
+    in_characters: &[u8],
+    bits: u64,
+    character_to_quintet: F,
+) -> Result<(), ZBase32Error>
+where
+    F: Fn(u8) -> Result<u8, ZBase32Error>,
+{
+    if in_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    let mut last_quintet = None;
+    for &character in in_characters {
+        last_quintet = Some(character_to_quintet(character)?);
+    }
+    match last_quintet {
+        Some(quintet) if !is_last_quintet_valid(bits, quintet) => Err(trailing_nonzero_bits()),
+        _ => Ok(()),
+    }
+}
+
+/// Decode a buffer of characters into a range of bits within a buffer of octets (bytes).
+///
+/// This method is similar to [`decode_slices`] except that the `bits` decoded bits
+/// are written starting at bit `start_bit` of `out_octets` instead of at its first
+/// bit. As with the rest of z-base-32, bits are counted starting from the _highest
+/// bit_ of the first octet. Bits of `out_octets` outside of the range are left
+/// unmodified. If an error is returned, bits within the range may have been modified.
+///
+/// The length of `in_characters` must match the value returned by
+/// [`required_quintets_buffer_len`] and the range must fit within `out_octets`.
+pub fn decode_into_bit_range(
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    start_bit: u64,
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    if in_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    check_bit_range(out_octets.len(), start_bit, bits)?;
+    let octet_iter =
+        QuintetsToOctetsIter::new(in_characters.iter().map(|&x| character_to_quintet(x)), bits);
+
+    for (index, next_octet) in octet_iter.enumerate() {
+        let bit = index as u64 * 8;
+        let count = (bits - bit).min(8) as u8;
+        write_octet_at_bit(out_octets, start_bit + bit, next_octet?, count);
     }
 
     Ok(())
 }
 
+/// Decode a buffer of characters to octets (bytes), reusing the same buffer for the
+/// output.
+///
+/// Decoding always shrinks the data, so the octets are written to the start of
+/// `buffer` as the characters are read. On success, the prefix of `buffer` holding
+/// the decoded octets is returned. If an error is returned, the contents of
+/// `buffer` are unspecified.
+///
+/// The length of `buffer` must match the value returned by
+/// [`required_quintets_buffer_len`].
+pub fn decode_in_place(buffer: &mut [u8], bits: u64) -> Result<&mut [u8], ZBase32Error> {
+    if buffer.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    let needed_octets = required_octets_buffer_len(bits)?;
+    {
+        // Each group of up to 5 octets is only written after all of the
+        // characters of its group have been read - and since a group of
+        // 8 characters always starts at or after the start of its octets,
+        // the writes never overtake the reads.
+        let cells = Cell::from_mut(&mut *buffer).as_slice_of_cells();
+
+        let octet_iter =
+            QuintetsToOctetsIter::new(cells.iter().map(|x| character_to_quintet(x.get())), bits);
+
+        for (out, next_octet) in cells.iter().zip(octet_iter) {
+            out.set(next_octet?);
+        }
+    }
+
+    Ok(&mut buffer[..needed_octets])
+}
+
+/// Lazily decode an iterator of characters into an iterator of octets (bytes).
+///
+/// The characters may be provided as either [`char`] or [`u8`] values. The returned
+/// iterator performs the same validation as [`decode_slices`]: an Err value is produced
+/// if `characters` doesn't produce the number of characters returned by
+/// [`required_quintets_buffer_len`], if a character is invalid, or if the last
+/// character has non-zero trailing bits. After producing an Err value, the returned
+/// iterator completes.
+///
+/// Characters are read from `characters` only as they are needed, with the exception
+/// that the character following the last expected character is read in order to check
+/// that the input has ended.
+pub fn decode_iter<I>(characters: I, bits: u64) -> impl Iterator<Item = Result<u8, ZBase32Error>>
+where
+    I: IntoIterator,
+    I::Item: Into<u32>,
+{
+    let quintet_iter = characters.into_iter().map(|character| {
+        let character: u32 = character.into();
+        if character > 0x7f {
+            Err(invalid_character())
+        } else {
+            character_to_quintet(character as u8)
+        }
+    });
+    QuintetsToOctetsIter::new(quintet_iter, bits)
+}
+
+/// Decode a buffer of characters to a buffer of octets (bytes), using multiple
+/// threads.
+///
+/// This method produces the same output as [`decode_slices`], including when the
+/// input is rejected, in which case the whole of `out_octets` is filled with zeros.
+/// Since every 8 characters decode to exactly 40 bits (5 octets), the input is
+/// split into chunks on those boundaries and each chunk is decoded independently
+/// on the [rayon](https://docs.rs/rayon) thread pool. Only the final chunk
+/// may contain a partial group and is subject to the trailing bit checks.
+///
+/// The length of `in_characters` must match the value returned by
+/// [`required_quintets_buffer_len`] and the length of `out_octets`
+/// must match the value returned by [`required_octets_buffer_len`].
+///
+/// This method is only available if the "rayon" feature is enabled.
+#[cfg(feature = "rayon")]
+pub fn par_decode_slices(
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    if in_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    if out_octets.len() != required_octets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+
+    let chunk_characters = PAR_CHUNK_GROUPS * QUINTETS_PER_GROUP;
+    let chunk_octets = PAR_CHUNK_GROUPS * OCTETS_PER_GROUP;
+    let last_chunk = in_characters.len().saturating_sub(1) / chunk_characters;
+
+    let result = in_characters
+        .par_chunks(chunk_characters)
+        .zip(out_octets.par_chunks_mut(chunk_octets))
+        .enumerate()
+        .try_for_each(|(index, (in_chunk, out_chunk))| {
+            let chunk_bits = if index == last_chunk {
+                bits - (index * chunk_octets) as u64 * 8
+            } else {
+                chunk_octets as u64 * 8
+            };
+            decode_slices(in_chunk, out_chunk, chunk_bits)
+        });
+    // Only the chunk that failed clears itself, so clear the other chunks too
+    if result.is_err() {
+        out_octets.fill(0);
+    }
+    result
+}
+
 /// Decode a slice of characters to a [`Vec`] of octets (bytes).
 ///
 /// The output octets will be appended to `output`.
 ///
 /// The length of `input` must match the value returned by
 /// [`required_quintets_buffer_len`]. The `output` buffer will be
-/// extended by to accommodate the output.
+/// extended by to accommodate the output. If an error is returned,
+/// `output` is restored to its original length.
 ///
 /// This method is not available in `no_std` mode.
 #[cfg(feature = "std")]
 pub fn decode(input: &str, output: &mut Vec<u8>, bits: u64) -> Result<(), ZBase32Error> {
+    decode_using(input.as_bytes(), output, bits, character_to_quintet)
+}
+
+/// Decode a slice of character values to a [`Vec`] of octets (bytes).
+///
+/// This method is the same as [`decode`] except that the input is a `&[u8]`,
+/// which avoids having to check that data read from a network connection or a
+/// file is valid UTF-8 first. Any values that aren't z-base-32 characters,
+/// including non-ASCII values, result in an InputError.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn decode_bytes_input(
+    input: &[u8],
+    output: &mut Vec<u8>,
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    decode_using(input, output, bits, character_to_quintet)
+}
+
+/// Decode a slice of characters to a newly allocated [`Vec`] of octets (bytes).
+///
+/// This method is the same as [`decode`] except that it returns the output
+/// instead of appending it to an existing `Vec`.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn decode_to_vec(input: &str, bits: u64) -> Result<Vec<u8>, ZBase32Error> {
+    let mut output = Vec::new();
+    decode(input, &mut output, bits)?;
+    Ok(output)
+}
+
+/// Decode a slice of characters to a [`Vec`] of octets (bytes), only accepting
+/// the exact lower-case output of [`encode`](crate::encode).
+///
+/// This method is the same as [`decode`] except that upper-case characters
+/// are rejected using [`character_to_quintet_strict`].
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn decode_strict(input: &str, output: &mut Vec<u8>, bits: u64) -> Result<(), ZBase32Error> {
+    decode_using(input.as_bytes(), output, bits, character_to_quintet_strict)
+}
+
+/// Decode a slice of characters to a [`Vec`] of octets (bytes) that is wiped when
+/// it is dropped.
+///
+/// This method is the same as [`decode`] except that the output is returned in a
+/// [`Zeroizing`] wrapper. The output is allocated up front with exactly the required
+/// capacity so that it is never reallocated, and if decoding fails partway through,
+/// the octets that were already written are wiped before the error is returned.
+/// The length of `input` is checked before anything is allocated.
+///
+/// Wiping is best-effort: see the "zeroize" feature in the crate documentation.
+///
+/// This method is only available if the "std" and "zeroize" features are enabled.
+#[cfg(all(feature = "std", feature = "zeroize"))]
+pub fn decode_zeroizing(input: &str, bits: u64) -> Result<Zeroizing<Vec<u8>>, ZBase32Error> {
     if input.len() != required_quintets_buffer_len(bits)? {
         return Err(input_buffer_doesnt_match_bits().into());
     }
-    let last_quintet_bits = if let Some(x) = calc_last_quintet_bits(bits) {
-        x
-    } else {
-        return Ok(());
-    };
+    let mut output = Zeroizing::new(Vec::with_capacity(required_octets_buffer_len(bits)?));
+    decode(input, &mut output, bits)?;
+    Ok(output)
+}
 
+#[cfg(feature = "std")]
+fn decode_using<F>(
+    input: &[u8],
+    output: &mut Vec<u8>,
+    bits: u64,
+    character_to_quintet: F,
+) -> Result<(), ZBase32Error>
+where
+    F: Fn(u8) -> Result<u8, ZBase32Error>,
+{
+    if input.len() != required_quintets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
     let needed_octets = required_octets_buffer_len(bits)?;
+    output.reserve(needed_octets);
     let start = output.len();
-    output.extend(std::iter::repeat(0).take(needed_octets));
-    let output_buff = &mut output[start..];
-
-    let octet_iter = QuintetsToOctetsIter::new(
-        input.as_bytes().iter().map(|&x| character_to_quintet(x)),
-        NeedQuintets::new(last_quintet_bits),
-    );
-
-    for (out, next_octet) in output_buff.iter_mut().zip(octet_iter) {
-        *out = next_octet?;
-    }
 
+    // The octets are decoded directly into the spare capacity, so the length of
+    // `output` is only changed once decoding has succeeded.
+    decode_uninit_using(
+        input,
+        &mut output.spare_capacity_mut()[..needed_octets],
+        bits,
+        character_to_quintet,
+    )?;
+    // SAFETY: decode_uninit_using initialized the first `needed_octets` elements
+    // of the spare capacity.
+    unsafe { output.set_len(start + needed_octets) };
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    use super::decode;
+    use super::{
+        character_to_quintet_strict, decode, decode_in_place, decode_into_bit_range, decode_iter,
+        decode_slices_strict, decode_slices_with_bit_order, decode_strict, is_canonical,
+        is_last_quintet_valid_with_bit_order, is_valid, possible_bits_for_input,
+        quintets_to_octets_with_bit_order, validate, validate_strict,
+    };
+    use crate::bit_order::BitOrder;
+    use crate::encode_impl::encode_slices_with_bit_order;
     use crate::test_data::{TestCase, RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+    use crate::ZBase32Error;
+    #[cfg(feature = "rayon")]
+    use {
+        super::{decode_slices, par_decode_slices},
+        crate::util::{required_octets_buffer_len, required_quintets_buffer_len},
+    };
 
     fn run_tests(test_cases: &[TestCase]) {
         let mut buffer = Vec::new();
@@ -282,4 +786,343 @@ mod tests {
     fn test_decode_random() {
         run_tests(RANDOM_TEST_DATA);
     }
+
+    #[test]
+    fn test_decode_error_restores_output() {
+        let mut buffer = vec![1, 2, 3];
+        // Invalid character after some octets have been decoded
+        assert!(decode("yysd!", &mut buffer, 25).is_err());
+        assert_eq!(buffer, [1, 2, 3]);
+        // Non-zero trailing bits in the last character
+        assert!(decode("yysdb", &mut buffer, 24).is_err());
+        assert_eq!(buffer, [1, 2, 3]);
+        // Input length that doesn't match the bits
+        assert!(decode("yysd", &mut buffer, 25).is_err());
+        assert_eq!(buffer, [1, 2, 3]);
+        assert!(decode_strict("yysdX", &mut buffer, 25).is_err());
+        assert_eq!(buffer, [1, 2, 3]);
+
+        decode("yysdx", &mut buffer, 25).unwrap();
+        assert_eq!(buffer, [1, 2, 3, 0, 44, 55, 128]);
+    }
+
+    #[test]
+    fn test_decode_slices_error_clears_output() {
+        use super::{decode_slices, decode_slices_strict};
+
+        let mut octets = [0xffu8; 4];
+        // Invalid character after some octets have been decoded
+        assert!(decode_slices(b"yysd!", &mut octets, 25).is_err());
+        assert_eq!(octets, [0; 4]);
+        // Non-zero trailing bits in the last character
+        let mut three_octets = [0xffu8; 3];
+        assert!(decode_slices(b"6n9hb", &mut three_octets, 24).is_err());
+        assert_eq!(three_octets, [0; 3]);
+        octets = [0xff; 4];
+        assert!(decode_slices_strict(b"yysdX", &mut octets, 25).is_err());
+        assert_eq!(octets, [0; 4]);
+
+        decode_slices(b"yysdx", &mut octets, 25).unwrap();
+        assert_eq!(octets, [0, 44, 55, 128]);
+    }
+
+    #[test]
+    fn test_decode_strict() {
+        let mut buffer = Vec::new();
+        decode_strict("yysdx", &mut buffer, 25).unwrap();
+        assert_eq!(buffer, &[0, 44, 55, 128]);
+
+        let err = decode_strict("yySdx", &mut buffer, 25).unwrap_err();
+        assert!(format!("{:?}", err).contains("case"));
+        let err = decode_strict("yy!dx", &mut buffer, 25).unwrap_err();
+        assert!(format!("{:?}", err).contains("Invalid character"));
+
+        assert_eq!(character_to_quintet_strict(b'y').unwrap(), 0);
+        assert_eq!(character_to_quintet_strict(b'1').unwrap(), 18);
+        assert!(character_to_quintet_strict(b'Y').is_err());
+        assert!(character_to_quintet_strict(b'L').is_err());
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut output = vec![0; test.unencoded.len()];
+            decode_slices_strict(test.encoded.as_bytes(), &mut output, test.bits).unwrap();
+            assert_eq!(output, test.unencoded);
+
+            let upper = test.encoded.to_uppercase();
+            if upper != test.encoded {
+                assert!(decode_slices_strict(upper.as_bytes(), &mut output, test.bits).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_possible_bits_for_input() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let range = possible_bits_for_input(test.encoded.as_bytes()).unwrap();
+            assert!(range.contains(&test.bits));
+            for bits in range {
+                assert!(is_valid(test.encoded.as_bytes(), bits));
+            }
+        }
+
+        assert_eq!(possible_bits_for_input(b"").unwrap(), 0..=0);
+        // 'y' is 0b00000, so any bit count may have produced it
+        assert_eq!(possible_bits_for_input(b"yy").unwrap(), 6..=10);
+        // 'w' is 0b10100, so at least 3 bits of it must be in use
+        assert_eq!(possible_bits_for_input(b"yw").unwrap(), 8..=10);
+        // '9' is 0b11111, so all 5 bits must be in use
+        assert_eq!(possible_bits_for_input(b"y9").unwrap(), 10..=10);
+        assert!(possible_bits_for_input(b"y!").is_err());
+    }
+
+    #[test]
+    fn test_validate() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            validate(test.encoded.as_bytes(), test.bits).unwrap();
+            validate(test.encoded.to_uppercase().as_bytes(), test.bits).unwrap();
+            validate_strict(test.encoded.as_bytes(), test.bits).unwrap();
+            assert!(is_valid(test.encoded.as_bytes(), test.bits));
+        }
+
+        validate(b"", 0).unwrap();
+        assert!(matches!(
+            validate(b"yysdx", 30),
+            Err(ZBase32Error::UsageError(_))
+        ));
+        let err = validate(b"yy!dx", 25).unwrap_err();
+        assert!(format!("{:?}", err).contains("Invalid character"));
+        let err = validate(b"yysdx", 24).unwrap_err();
+        assert!(format!("{:?}", err).contains("Trailing"));
+        let err = validate_strict(b"yySdx", 25).unwrap_err();
+        assert!(format!("{:?}", err).contains("case"));
+        assert!(!is_valid(b"yysdx", 24));
+        assert!(is_valid(b"YYSDX", 25));
+    }
+
+    #[test]
+    fn test_is_canonical() {
+        assert!(is_canonical(b"yysdx", 25));
+        assert!(is_canonical(b"", 0));
+        assert!(!is_canonical(b"YYSDX", 25));
+        assert!(!is_canonical(b"yysdx", 24));
+        assert!(!is_canonical(b"yysdx", 30));
+        assert!(!is_canonical(b"yysdb", 24));
+        assert!(!is_canonical(b"yys!x", 25));
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            assert!(is_canonical(test.encoded.as_bytes(), test.bits));
+        }
+    }
+
+    #[test]
+    fn test_decode_lsb_first() {
+        let mut buffer = [0; 1];
+        decode_slices_with_bit_order(b"98", &mut buffer, 8, BitOrder::LsbFirst).unwrap();
+        assert_eq!(&buffer, &[0xff]);
+        decode_slices_with_bit_order(b"bb", &mut buffer, 8, BitOrder::LsbFirst).unwrap();
+        assert_eq!(&buffer, &[0x21]);
+        decode_slices_with_bit_order(b"b", &mut buffer, 1, BitOrder::LsbFirst).unwrap();
+        assert_eq!(&buffer, &[0x01]);
+        assert!(decode_slices_with_bit_order(b"o", &mut buffer, 1, BitOrder::LsbFirst).is_err());
+        assert!(decode_slices_with_bit_order(b"9h", &mut buffer, 8, BitOrder::LsbFirst).is_err());
+        quintets_to_octets_with_bit_order(&[1], &mut buffer, 1, BitOrder::LsbFirst).unwrap();
+        assert_eq!(&buffer, &[0x01]);
+        assert!(is_last_quintet_valid_with_bit_order(
+            1,
+            1,
+            BitOrder::LsbFirst
+        ));
+        assert!(!is_last_quintet_valid_with_bit_order(
+            1,
+            16,
+            BitOrder::LsbFirst
+        ));
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let input: Vec<u8> = test.unencoded.iter().map(|x| x.reverse_bits()).collect();
+            let mut characters = vec![0; test.encoded.len()];
+            encode_slices_with_bit_order(&input, &mut characters, test.bits, BitOrder::LsbFirst)
+                .unwrap();
+            let mut output = vec![0; input.len()];
+            decode_slices_with_bit_order(&characters, &mut output, test.bits, BitOrder::LsbFirst)
+                .unwrap();
+            assert_eq!(output, input);
+        }
+    }
+
+    #[test]
+    fn test_decode_into_bit_range() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            for &start_bit in &[0, 1, 3, 8, 13] {
+                for &fill in &[0x00, 0xff] {
+                    let mut buffer = vec![fill; test.unencoded.len() + 3];
+                    decode_into_bit_range(
+                        test.encoded.as_bytes(),
+                        &mut buffer,
+                        start_bit,
+                        test.bits,
+                    )
+                    .unwrap();
+                    for bit in 0..(buffer.len() as u64 * 8) {
+                        let actual = buffer[(bit / 8) as usize] & (0x80 >> (bit % 8)) != 0;
+                        let expected = if bit >= start_bit && bit < start_bit + test.bits {
+                            let bit = bit - start_bit;
+                            test.unencoded[(bit / 8) as usize] & (0x80 >> (bit % 8)) != 0
+                        } else {
+                            fill != 0
+                        };
+                        assert_eq!(actual, expected);
+                    }
+                }
+            }
+        }
+
+        let mut buffer = vec![0; 2];
+        assert!(decode_into_bit_range(b"yy", &mut buffer, 7, 10).is_err());
+        assert!(decode_into_bit_range(b"yy", &mut buffer, u64::MAX, 10).is_err());
+    }
+
+    #[test]
+    fn test_decode_in_place() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = test.encoded.as_bytes().to_vec();
+            let decoded = decode_in_place(&mut buffer, test.bits).unwrap();
+            assert_eq!(decoded, test.unencoded);
+        }
+
+        let mut buffer = b"yyyyyyyyyyyyyyy!".to_vec();
+        assert!(decode_in_place(&mut buffer, 80).is_err());
+        let mut buffer = b"yyyyyyyyyyyyyyy".to_vec();
+        assert!(decode_in_place(&mut buffer, 80).is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_decode_slices() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = vec![0; test.unencoded.len()];
+            par_decode_slices(test.encoded.as_bytes(), &mut buffer, test.bits).unwrap();
+            assert_eq!(&buffer[..], test.unencoded);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_decode_slices_multiple_chunks() {
+        const ALPHABET: &[u8] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+        for &bits in &[800_000, 800_020, 800_024] {
+            let mut characters: Vec<u8> = (0..required_quintets_buffer_len(bits).unwrap())
+                .map(|x| ALPHABET[(x * 7 + x / 13) % 32])
+                .collect();
+            if bits % 5 != 0 {
+                // Use a final character whose trailing bits are all zero
+                *characters.last_mut().unwrap() = b'y';
+            }
+            let octets = required_octets_buffer_len(bits).unwrap();
+            let mut expected = vec![0; octets];
+            decode_slices(&characters, &mut expected, bits).unwrap();
+            let mut actual = vec![0; octets];
+            par_decode_slices(&characters, &mut actual, bits).unwrap();
+            assert_eq!(actual, expected);
+        }
+
+        let mut characters = vec![b'b'; 100_000];
+        characters[50_000] = b'!';
+        let mut output = vec![0xff; 62_500];
+        assert!(par_decode_slices(&characters, &mut output, 500_000).is_err());
+        assert!(output.iter().all(|&x| x == 0));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_decode_slices_matches_sequential_on_error() {
+        // An invalid character in the first of several chunks, so that the later
+        // chunks decode successfully
+        let mut characters = vec![b'b'; 100_000];
+        characters[10] = b'!';
+        let mut expected = vec![0xff; 62_500];
+        let expected_result = decode_slices(&characters, &mut expected, 500_000);
+        let mut actual = vec![0xff; 62_500];
+        let actual_result = par_decode_slices(&characters, &mut actual, 500_000);
+        assert!(expected_result.is_err() && actual_result.is_err());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_decode_iter() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let decoded: Result<Vec<u8>, ZBase32Error> =
+                decode_iter(test.encoded.chars(), test.bits).collect();
+            assert_eq!(decoded.unwrap(), test.unencoded);
+            let decoded: Result<Vec<u8>, ZBase32Error> =
+                decode_iter(test.encoded.bytes(), test.bits).collect();
+            assert_eq!(decoded.unwrap(), test.unencoded);
+        }
+
+        assert_eq!(decode_iter("".chars(), 0).count(), 0);
+
+        // Too few, too many, invalid characters, and non-zero trailing bits
+        for (encoded, bits) in [
+            ("yys", 25),
+            ("yysdxy", 25),
+            ("yysd\u{e9}", 25),
+            ("yysdb", 24),
+            ("y", 0),
+        ]
+        .iter()
+        {
+            let results: Vec<_> = decode_iter(encoded.chars(), *bits).collect();
+            assert!(results.last().unwrap().is_err());
+            assert_eq!(results.iter().filter(|x| x.is_err()).count(), 1);
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_decode_zeroizing() {
+        use super::decode_zeroizing;
+
+        let decoded = decode_zeroizing("yysdx", 25).unwrap();
+        assert_eq!(&decoded[..], &[0, 44, 55, 128]);
+        assert_eq!(decoded.capacity(), 4);
+
+        assert!(decode_zeroizing("yysd!", 25).is_err());
+        assert!(decode_zeroizing("yysdx", 24).is_err());
+        assert!(decode_zeroizing("yy", 1 << 60).is_err());
+    }
+
+    #[test]
+    fn test_decode_uninit() {
+        use super::decode_uninit;
+        use core::mem::MaybeUninit;
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = vec![MaybeUninit::uninit(); test.unencoded.len()];
+            let decoded = decode_uninit(test.encoded.as_bytes(), &mut buffer, test.bits).unwrap();
+            assert_eq!(decoded, test.unencoded);
+        }
+
+        let mut buffer = [MaybeUninit::uninit(); 4];
+        assert!(decode_uninit(b"yysd!", &mut buffer, 25).is_err());
+        assert!(decode_uninit(b"yysdx", &mut buffer[..3], 25).is_err());
+    }
+
+    #[test]
+    fn test_decode_bytes_input_and_to_vec() {
+        use super::{decode_bytes_input, decode_to_vec};
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = vec![1];
+            decode_bytes_input(test.encoded.as_bytes(), &mut buffer, test.bits).unwrap();
+            assert_eq!(&buffer[1..], test.unencoded);
+            assert_eq!(
+                decode_to_vec(test.encoded, test.bits).unwrap(),
+                test.unencoded
+            );
+        }
+
+        let mut buffer = vec![1];
+        assert!(decode_bytes_input(b"yysd\xff", &mut buffer, 25).is_err());
+        assert!(decode_bytes_input(b"yysdb", &mut buffer, 24).is_err());
+        assert_eq!(buffer, [1]);
+        assert!(decode_to_vec("yysd", 25).is_err());
+    }
 }
@@ -1,24 +1,38 @@
+use crate::bit_order::BitOrder;
+use crate::case::Case;
 use crate::error::{
     input_buffer_doesnt_match_bits, invalid_quintet, output_buffer_doesnt_match_bits,
+    trailing_nonzero_bits,
 };
 use crate::stateful_encoder::{
     octet_has_valid_trailing_bits, HaveQuintets, NeedOctets, NextQuintetResult, ProvideOctetResult,
 };
 use crate::tables::QUINTET_TO_CHARACTER;
-use crate::util::{required_octets_buffer_len, required_quintets_buffer_len};
+use crate::util::{
+    check_bit_range, fill_uninit, read_octet_at_bit, required_octets_buffer_len,
+    required_quintets_buffer_len,
+};
+#[cfg(feature = "rayon")]
+use crate::util::{OCTETS_PER_GROUP, PAR_CHUNK_GROUPS, QUINTETS_PER_GROUP};
 use crate::ZBase32Error;
-use core::iter::Peekable;
+use core::mem::MaybeUninit;
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(all(feature = "std", feature = "zeroize"))]
+use zeroize::Zeroizing;
 
 enum OctetsToQuintetsIterState {
     Initial(NeedOctets),
     HaveQuintets(HaveQuintets),
+    Failed(ZBase32Error),
 }
 
 struct OctetsToQuintetsIter<I>
 where
     I: Iterator<Item = u8>,
 {
-    octet_iter: Peekable<I>,
+    octet_iter: I,
+    remaining_octets: usize,
     state: Option<OctetsToQuintetsIterState>,
 }
 
@@ -26,24 +40,54 @@ impl<I> OctetsToQuintetsIter<I>
 where
     I: Iterator<Item = u8>,
 {
-    fn new(octet_iter: I, need_octets: NeedOctets) -> OctetsToQuintetsIter<I> {
+    // The iterator produces an error if `octet_iter` doesn't produce exactly
+    // the number of octets required for `bits`.
+    fn new(mut octet_iter: I, bits: u64) -> OctetsToQuintetsIter<I> {
+        let (remaining_octets, state) =
+            match (required_octets_buffer_len(bits), calc_last_octet_bits(bits)) {
+                (Ok(octets), Some(last_octet_bits)) => (
+                    octets,
+                    Some(OctetsToQuintetsIterState::Initial(NeedOctets::new(
+                        last_octet_bits,
+                    ))),
+                ),
+                (Ok(_), None) => match octet_iter.next() {
+                    Some(_) => (
+                        0,
+                        Some(OctetsToQuintetsIterState::Failed(
+                            input_buffer_doesnt_match_bits().into(),
+                        )),
+                    ),
+                    None => (0, None),
+                },
+                (Err(err), _) => (0, Some(OctetsToQuintetsIterState::Failed(err.into()))),
+            };
         OctetsToQuintetsIter {
-            octet_iter: octet_iter.peekable(),
-            state: Some(OctetsToQuintetsIterState::Initial(need_octets)),
+            octet_iter,
+            remaining_octets,
+            state,
         }
     }
 }
 
 fn refill<I>(
-    octet_iter: &mut Peekable<I>,
+    octet_iter: &mut I,
+    remaining_octets: &mut usize,
     mut need_octets: NeedOctets,
 ) -> Result<Option<OctetsToQuintetsIterState>, ZBase32Error>
 where
     I: Iterator<Item = u8>,
 {
     loop {
-        let octet = octet_iter.next().unwrap();
-        let last_octet = octet_iter.peek().is_none();
+        let octet = match octet_iter.next() {
+            Some(octet) => octet,
+            None => return Err(input_buffer_doesnt_match_bits().into()),
+        };
+        *remaining_octets -= 1;
+        let last_octet = *remaining_octets == 0;
+        if last_octet && octet_iter.next().is_some() {
+            return Err(input_buffer_doesnt_match_bits().into());
+        }
         match need_octets.provide_octet(octet, last_octet)? {
             ProvideOctetResult::NeedOctets(need_more) => need_octets = need_more,
             ProvideOctetResult::HaveQuintets(have_quintets) => {
@@ -67,25 +111,24 @@ where
 
             match self.state.take().unwrap() {
                 // The first time we are called, we start in the initial state
-                // and have to read some quintets from the underlying iterator.
+                // and have to read some octets from the underlying iterator.
                 // After doing this, we'll loop and never end up in this state
                 // again.
                 OctetsToQuintetsIterState::Initial(need_octets) => {
-                    if self.octet_iter.peek().is_some() {
-                        match refill(&mut self.octet_iter, need_octets) {
-                            Ok(new_state) => self.state = new_state,
-                            Err(err) => return Some(Err(err)),
-                        }
-                    } else {
-                        // Do nothing - this means that the input iterator was empty -
-                        // which shouldn't actually be possible.
+                    match refill(
+                        &mut self.octet_iter,
+                        &mut self.remaining_octets,
+                        need_octets,
+                    ) {
+                        Ok(new_state) => self.state = new_state,
+                        Err(err) => return Some(Err(err)),
                     }
                 }
 
-                // If we are in the HaveOctets state, it means we can try to return
-                // already processed octet values. If we run out of octets, we will
-                // need to use the refill method to process more quintets into
-                // octets. If we hit the end of the input, we'll leave self.state
+                // If we are in the HaveQuintets state, it means we can try to return
+                // already processed quintet values. If we run out of quintets, we will
+                // need to use the refill method to process more octets into
+                // quintets. If we hit the end of the input, we'll leave self.state
                 // as None as a signal to complete.
                 OctetsToQuintetsIterState::HaveQuintets(have_quintets) => {
                     match have_quintets.next_quintet() {
@@ -95,7 +138,11 @@ where
                             return Some(Ok(quintet));
                         }
                         NextQuintetResult::NeedOctets(need_octets) => {
-                            match refill(&mut self.octet_iter, need_octets) {
+                            match refill(
+                                &mut self.octet_iter,
+                                &mut self.remaining_octets,
+                                need_octets,
+                            ) {
                                 Ok(new_state) => self.state = new_state,
                                 Err(err) => return Some(Err(err)),
                             }
@@ -103,6 +150,10 @@ where
                         NextQuintetResult::Complete => {}
                     }
                 }
+
+                // If the iterator couldn't be started, we return the error once
+                // and then complete.
+                OctetsToQuintetsIterState::Failed(err) => return Some(Err(err)),
             }
         }
     }
@@ -111,10 +162,16 @@ where
 /// Convert a quintet integer value (such as "0") to its character
 /// value (such as "y").
 pub fn quintet_to_character(quintet: u8) -> Result<u8, ZBase32Error> {
-    if quintet as usize > QUINTET_TO_CHARACTER.len() {
+    quintet_to_character_with_case(quintet, Case::Lower)
+}
+
+/// Convert a quintet integer value (such as "0") to its character
+/// value (such as "y" or "Y") using the specified [`Case`].
+pub fn quintet_to_character_with_case(quintet: u8, case: Case) -> Result<u8, ZBase32Error> {
+    if quintet as usize >= QUINTET_TO_CHARACTER.len() {
         return Err(invalid_quintet());
     }
-    Ok(QUINTET_TO_CHARACTER[quintet as usize])
+    Ok(case.convert_character(QUINTET_TO_CHARACTER[quintet as usize]))
 }
 
 fn calc_last_octet_bits(bits: u64) -> Option<u8> {
@@ -130,8 +187,14 @@ fn calc_last_octet_bits(bits: u64) -> Option<u8> {
 
 /// Determine if the last octet is valid, given the number of bits to encode
 pub fn is_last_octet_valid(bits: u64, octet: u8) -> bool {
+    is_last_octet_valid_with_bit_order(bits, octet, BitOrder::MsbFirst)
+}
+
+/// Determine if the last octet is valid, given the number of bits to encode
+/// and the [`BitOrder`] of the octet.
+pub fn is_last_octet_valid_with_bit_order(bits: u64, octet: u8, bit_order: BitOrder) -> bool {
     if let Some(last_octet_bits) = calc_last_octet_bits(bits) {
-        octet_has_valid_trailing_bits(last_octet_bits, octet)
+        octet_has_valid_trailing_bits(last_octet_bits, bit_order.convert_octet(octet))
     } else {
         false
     }
@@ -146,6 +209,20 @@ pub fn octets_to_quintets(
     in_octets: &[u8],
     out_quintets: &mut [u8],
     bits: u64,
+) -> Result<(), ZBase32Error> {
+    octets_to_quintets_with_bit_order(in_octets, out_quintets, bits, BitOrder::MsbFirst)
+}
+
+/// Convert a buffer of octets (bytes) into a buffer of quintet values (ie, integers between 0-31)
+/// using the specified [`BitOrder`].
+///
+/// This method is the same as [`octets_to_quintets`] except that both the
+/// octets and the quintets use the bit order `bit_order`.
+pub fn octets_to_quintets_with_bit_order(
+    in_octets: &[u8],
+    out_quintets: &mut [u8],
+    bits: u64,
+    bit_order: BitOrder,
 ) -> Result<(), ZBase32Error> {
     if in_octets.len() != required_octets_buffer_len(bits)? {
         return Err(input_buffer_doesnt_match_bits().into());
@@ -153,19 +230,12 @@ pub fn octets_to_quintets(
     if out_quintets.len() != required_quintets_buffer_len(bits)? {
         return Err(output_buffer_doesnt_match_bits().into());
     }
-    let last_octet_bits = if let Some(x) = calc_last_octet_bits(bits) {
-        x
-    } else {
-        return Ok(());
-    };
 
-    let quintet_iter = OctetsToQuintetsIter::new(
-        in_octets.iter().map(|&x| x),
-        NeedOctets::new(last_octet_bits),
-    );
+    let quintet_iter =
+        OctetsToQuintetsIter::new(in_octets.iter().map(|&x| bit_order.convert_octet(x)), bits);
 
     for (out_quintet, in_quintet) in out_quintets.iter_mut().zip(quintet_iter) {
-        *out_quintet = in_quintet?;
+        *out_quintet = bit_order.convert_quintet(in_quintet?);
     }
 
     Ok(())
@@ -186,10 +256,54 @@ pub fn octets_to_quintets(
 /// The length of `in_octets` must match the value returned by
 /// [`required_octets_buffer_len`] and the length of `out_characters`
 /// must match the value returned by [`required_quintets_buffer_len`].
+///
+/// If the input is rejected, the whole of `out_characters` is filled with zeros,
+/// including any characters that hadn't been written yet, so that partial output
+/// is never left behind. Usage errors, such as buffer lengths that don't match
+/// `bits`, are returned before anything is written.
 pub fn encode_slices(
     in_octets: &[u8],
     out_characters: &mut [u8],
     bits: u64,
+) -> Result<(), ZBase32Error> {
+    encode_slices_with_bit_order(in_octets, out_characters, bits, BitOrder::MsbFirst)
+}
+
+/// Encode a buffer of octets (bytes) to a possibly uninitialized buffer of characters.
+///
+/// This method is the same as [`encode_slices`] except that `out_characters` doesn't
+/// need to be initialized, which avoids zero-filling a buffer that is about to be
+/// overwritten. On success, `out_characters` is returned as an initialized slice.
+///
+/// The length of `in_octets` must match the value returned by
+/// [`required_octets_buffer_len`] and the length of `out_characters`
+/// must match the value returned by [`required_quintets_buffer_len`].
+pub fn encode_uninit<'a>(
+    in_octets: &[u8],
+    out_characters: &'a mut [MaybeUninit<u8>],
+    bits: u64,
+) -> Result<&'a mut [u8], ZBase32Error> {
+    if in_octets.len() != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    if out_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+    let character_iter = OctetsToQuintetsIter::new(in_octets.iter().copied(), bits)
+        .map(|quintet| quintet.and_then(quintet_to_character));
+    fill_uninit(out_characters, character_iter)
+}
+
+/// Encode a buffer of octets (bytes) to a buffer of characters using the
+/// specified [`BitOrder`].
+///
+/// This method is the same as [`encode_slices`] except that the bits of
+/// each octet and each quintet are taken in the order `bit_order`.
+pub fn encode_slices_with_bit_order(
+    in_octets: &[u8],
+    out_characters: &mut [u8],
+    bits: u64,
+    bit_order: BitOrder,
 ) -> Result<(), ZBase32Error> {
     if in_octets.len() != required_octets_buffer_len(bits)? {
         return Err(input_buffer_doesnt_match_bits().into());
@@ -197,16 +311,81 @@ pub fn encode_slices(
     if out_characters.len() != required_quintets_buffer_len(bits)? {
         return Err(output_buffer_doesnt_match_bits().into());
     }
+
+    let quintet_iter =
+        OctetsToQuintetsIter::new(in_octets.iter().map(|&x| bit_order.convert_octet(x)), bits);
+
+    let mut result = Ok(());
+    for (out_quintet, in_quintet) in out_characters.iter_mut().zip(quintet_iter) {
+        match in_quintet.and_then(|x| quintet_to_character(bit_order.convert_quintet(x))) {
+            Ok(character) => *out_quintet = character,
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+    if result.is_err() {
+        out_characters.fill(0);
+    }
+
+    result
+}
+
+/// Encode a buffer of octets (bytes) to a buffer of characters using the
+/// specified [`Case`].
+///
+/// This method is the same as [`encode_slices`] except that the output characters
+/// use the case `case`.
+pub fn encode_slices_with_case(
+    in_octets: &[u8],
+    out_characters: &mut [u8],
+    bits: u64,
+    case: Case,
+) -> Result<(), ZBase32Error> {
+    encode_slices(in_octets, out_characters, bits)?;
+    case.convert_characters(out_characters);
+    Ok(())
+}
+
+/// Encode a range of bits from a buffer of octets (bytes) to a buffer of characters.
+///
+/// This method is similar to [`encode_slices`] except that the `bits` bits to encode
+/// start at bit `start_bit` of `input` instead of at its first bit. As with the
+/// rest of z-base-32, bits are counted starting from the _highest bit_ of the
+/// first octet. Bits of `input` outside of the range are ignored, so unlike
+/// [`encode_slices`], non-zero bits following the range aren't an error.
+///
+/// The range must fit within `input` and the length of `out_characters`
+/// must match the value returned by [`required_quintets_buffer_len`].
+pub fn encode_bit_range(
+    input: &[u8],
+    start_bit: u64,
+    bits: u64,
+    out_characters: &mut [u8],
+) -> Result<(), ZBase32Error> {
+    check_bit_range(input.len(), start_bit, bits)?;
+    if out_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
     let last_octet_bits = if let Some(x) = calc_last_octet_bits(bits) {
         x
     } else {
         return Ok(());
     };
 
-    let quintet_iter = OctetsToQuintetsIter::new(
-        in_octets.iter().map(|&x| x),
-        NeedOctets::new(last_octet_bits),
-    );
+    let needed_octets = required_octets_buffer_len(bits)?;
+    let trailing_bits_mask = 0xffu8.checked_shr(last_octet_bits as u32).unwrap_or(0);
+    let octet_iter = (0..needed_octets).map(|index| {
+        let octet = read_octet_at_bit(input, start_bit + index as u64 * 8);
+        if index + 1 == needed_octets {
+            octet & !trailing_bits_mask
+        } else {
+            octet
+        }
+    });
+
+    let quintet_iter = OctetsToQuintetsIter::new(octet_iter, bits);
 
     for (out_quintet, in_quintet) in out_characters.iter_mut().zip(quintet_iter) {
         *out_quintet = quintet_to_character(in_quintet?)?;
@@ -215,43 +394,253 @@ pub fn encode_slices(
     Ok(())
 }
 
+/// Encode octets (bytes) to characters, reusing the same buffer for the output.
+///
+/// The first `data_len` bytes of `buffer` hold the octets to encode. Encoding grows
+/// the data, so `buffer` must be at least as long as the value returned by
+/// [`required_quintets_buffer_len`]. The input is encoded one 40-bit group at a time,
+/// starting from the end, so that no group is overwritten before it has been read.
+/// On success, the prefix of `buffer` holding the encoded characters is returned.
+///
+/// The only input that can be rejected is the final octet, which is checked before
+/// anything is written - so, if an error is returned, `buffer` is left unmodified.
+///
+/// `data_len` must match the value returned by [`required_octets_buffer_len`].
+pub fn encode_in_place(
+    buffer: &mut [u8],
+    data_len: usize,
+    bits: u64,
+) -> Result<&mut [u8], ZBase32Error> {
+    if data_len != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    let needed_quintets = required_quintets_buffer_len(bits)?;
+    if buffer.len() < needed_quintets {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+    if bits == 0 {
+        return Ok(&mut buffer[..0]);
+    }
+    // Since `encode_slices` clears its output on error, the final octet has to
+    // be checked before the group that holds it is encoded over it
+    if !is_last_octet_valid(bits, buffer[data_len - 1]) {
+        return Err(trailing_nonzero_bits());
+    }
+
+    let last_group = (data_len - 1) / 5;
+    for group in (0..=last_group).rev() {
+        let octets_start = group * 5;
+        let octets_end = data_len.min(octets_start + 5);
+        let characters_start = group * 8;
+        let characters_end = needed_quintets.min(characters_start + 8);
+        let group_bits = if group == last_group {
+            bits - group as u64 * 40
+        } else {
+            40
+        };
+
+        let mut octets = [0u8; 5];
+        let octets = &mut octets[..octets_end - octets_start];
+        octets.copy_from_slice(&buffer[octets_start..octets_end]);
+
+        encode_slices(
+            octets,
+            &mut buffer[characters_start..characters_end],
+            group_bits,
+        )?;
+    }
+
+    Ok(&mut buffer[..needed_quintets])
+}
+
+/// Lazily encode an iterator of octets (bytes) into an iterator of characters.
+///
+/// The returned iterator performs the same validation as [`encode_slices`]: an
+/// Err value is produced if `octets` doesn't produce the number of octets returned by
+/// [`required_octets_buffer_len`] or if the last octet has non-zero trailing bits.
+/// After producing an Err value, the returned iterator completes.
+///
+/// Octets are read from `octets` only as they are needed, with the exception that
+/// the octet following the last expected octet is read in order to check that the
+/// input has ended.
+pub fn encode_iter<I>(octets: I, bits: u64) -> impl Iterator<Item = Result<char, ZBase32Error>>
+where
+    I: IntoIterator<Item = u8>,
+{
+    OctetsToQuintetsIter::new(octets.into_iter(), bits)
+        .map(|quintet| quintet.and_then(quintet_to_character).map(char::from))
+}
+
+/// Encode a buffer of octets (bytes) to a buffer of characters, using multiple
+/// threads.
+///
+/// This method produces the same output as [`encode_slices`], including when the
+/// input is rejected, in which case the whole of `out_characters` is filled with
+/// zeros. Since every 40-bit group of input (5 octets) encodes to exactly 8
+/// characters, the input is split into chunks on those boundaries and each chunk is
+/// encoded independently on the [rayon](https://docs.rs/rayon) thread pool. Only
+/// the final chunk may contain a partial group and is subject to the trailing bit
+/// checks.
+///
+/// The length of `in_octets` must match the value returned by
+/// [`required_octets_buffer_len`] and the length of `out_characters`
+/// must match the value returned by [`required_quintets_buffer_len`].
+///
+/// This method is only available if the "rayon" feature is enabled.
+#[cfg(feature = "rayon")]
+pub fn par_encode_slices(
+    in_octets: &[u8],
+    out_characters: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    if in_octets.len() != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    if out_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+
+    let chunk_octets = PAR_CHUNK_GROUPS * OCTETS_PER_GROUP;
+    let chunk_characters = PAR_CHUNK_GROUPS * QUINTETS_PER_GROUP;
+    let last_chunk = in_octets.len().saturating_sub(1) / chunk_octets;
+
+    let result = in_octets
+        .par_chunks(chunk_octets)
+        .zip(out_characters.par_chunks_mut(chunk_characters))
+        .enumerate()
+        .try_for_each(|(index, (in_chunk, out_chunk))| {
+            let chunk_bits = if index == last_chunk {
+                bits - (index * chunk_octets) as u64 * 8
+            } else {
+                chunk_octets as u64 * 8
+            };
+            encode_slices(in_chunk, out_chunk, chunk_bits)
+        });
+    // Only the chunk that failed clears itself, so clear the other chunks too
+    if result.is_err() {
+        out_characters.fill(0);
+    }
+    result
+}
+
 /// Encode a slice of octets (bytes) to a [`String`].
 ///
 /// The output characters will be appended to `output`.
 ///
 /// The length of `input` must match the value returned by
 /// [`required_octets_buffer_len`]. The `output` buffer will be
-/// extended by to accommodate the output.
+/// extended by to accommodate the output. If an error is returned,
+/// `output` is restored to its original length.
 ///
 /// This method is not available in `no_std` mode.
 #[cfg(feature = "std")]
 pub fn encode(input: &[u8], output: &mut String, bits: u64) -> Result<(), ZBase32Error> {
+    // SAFETY: encode_to_vec only appends ASCII characters, so the String always
+    // holds valid UTF-8.
+    encode_to_vec(input, unsafe { output.as_mut_vec() }, bits)
+}
+
+/// Encode a slice of octets (bytes) to a [`Vec`] of character values.
+///
+/// This method is the same as [`encode`] except that the output characters are
+/// appended to a `Vec<u8>`, which is convenient when the output is going to be
+/// written to a network connection or a file.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_to_vec(input: &[u8], output: &mut Vec<u8>, bits: u64) -> Result<(), ZBase32Error> {
     if input.len() != required_octets_buffer_len(bits)? {
         return Err(input_buffer_doesnt_match_bits().into());
     }
-    let last_octet_bits = if let Some(x) = calc_last_octet_bits(bits) {
-        x
-    } else {
-        return Ok(());
-    };
-
     let needed_quintets = required_quintets_buffer_len(bits)?;
     output.reserve(needed_quintets);
+    let start = output.len();
 
-    let quintet_iter =
-        OctetsToQuintetsIter::new(input.iter().map(|&x| x), NeedOctets::new(last_octet_bits));
+    // The characters are encoded directly into the spare capacity, so the length
+    // of `output` is only changed once encoding has succeeded.
+    encode_uninit(
+        input,
+        &mut output.spare_capacity_mut()[..needed_quintets],
+        bits,
+    )?;
+    // SAFETY: encode_uninit initialized the first `needed_quintets` elements
+    // of the spare capacity.
+    unsafe { output.set_len(start + needed_quintets) };
+    Ok(())
+}
 
-    for quintet in quintet_iter {
-        output.push(quintet_to_character(quintet?)? as char);
-    }
+/// Encode a slice of octets (bytes) to a newly allocated [`String`].
+///
+/// This method is the same as [`encode`] except that it returns the output
+/// instead of appending it to an existing `String`.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_to_string(input: &[u8], bits: u64) -> Result<String, ZBase32Error> {
+    let mut output = String::new();
+    encode(input, &mut output, bits)?;
+    Ok(output)
+}
 
+/// Encode a slice of octets (bytes) to a [`String`] using the specified [`Case`].
+///
+/// This method is the same as [`encode`] except that the output characters
+/// use the case `case`.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_with_case(
+    input: &[u8],
+    output: &mut String,
+    bits: u64,
+    case: Case,
+) -> Result<(), ZBase32Error> {
+    let start = output.len();
+    encode(input, output, bits)?;
+    if case == Case::Upper {
+        output[start..].make_ascii_uppercase();
+    }
     Ok(())
 }
 
+/// Encode a slice of octets (bytes) to a [`String`] that is wiped when it is dropped.
+///
+/// This method is the same as [`encode`] except that the output is returned in a
+/// [`Zeroizing`] wrapper. The output is allocated up front with exactly the required
+/// capacity so that it is never reallocated, which would leave a copy of the
+/// characters behind in the old allocation. The length of `input` is checked before
+/// anything is allocated.
+///
+/// Wiping is best-effort: see the "zeroize" feature in the crate documentation.
+///
+/// This method is only available if the "std" and "zeroize" features are enabled.
+#[cfg(all(feature = "std", feature = "zeroize"))]
+pub fn encode_zeroizing(input: &[u8], bits: u64) -> Result<Zeroizing<String>, ZBase32Error> {
+    if input.len() != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    let mut output = Zeroizing::new(String::with_capacity(required_quintets_buffer_len(bits)?));
+    encode(input, &mut output, bits)?;
+    Ok(output)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::encode;
+    use super::{
+        encode, encode_bit_range, encode_in_place, encode_iter, encode_slices_with_bit_order,
+        encode_slices_with_case, encode_with_case, is_last_octet_valid_with_bit_order,
+        octets_to_quintets_with_bit_order, quintet_to_character, quintet_to_character_with_case,
+    };
+    use crate::bit_order::BitOrder;
+    use crate::case::Case;
+    use crate::tables::QUINTET_TO_CHARACTER;
     use crate::test_data::{TestCase, RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+    use crate::ZBase32Error;
+    #[cfg(feature = "rayon")]
+    use {
+        super::{encode_slices, par_encode_slices},
+        crate::util::{required_octets_buffer_len, required_quintets_buffer_len},
+    };
 
     fn run_tests(test_cases: &[TestCase]) {
         let mut buffer = String::new();
@@ -271,4 +660,282 @@ mod tests {
     fn test_encode_random() {
         run_tests(RANDOM_TEST_DATA);
     }
+
+    #[test]
+    fn test_encode_upper_case() {
+        let mut buffer = String::from("prefix:");
+        encode_with_case(&[0, 44, 55, 128], &mut buffer, 25, Case::Upper).unwrap();
+        assert_eq!(buffer, "prefix:YYSDX");
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut characters = vec![0; test.encoded.len()];
+            encode_slices_with_case(test.unencoded, &mut characters, test.bits, Case::Upper)
+                .unwrap();
+            assert_eq!(characters, test.encoded.to_uppercase().as_bytes());
+            encode_slices_with_case(test.unencoded, &mut characters, test.bits, Case::Lower)
+                .unwrap();
+            assert_eq!(characters, test.encoded.as_bytes());
+        }
+
+        assert_eq!(
+            quintet_to_character_with_case(0, Case::Upper).unwrap(),
+            b'Y'
+        );
+        assert_eq!(
+            quintet_to_character_with_case(18, Case::Upper).unwrap(),
+            b'1'
+        );
+    }
+
+    #[test]
+    fn test_quintet_to_character_out_of_range() {
+        // 32 is one past the end of the alphabet, which used to panic instead
+        // of returning an error
+        for quintet in 32..=255 {
+            assert!(quintet_to_character(quintet).is_err());
+            assert!(quintet_to_character_with_case(quintet, Case::Upper).is_err());
+        }
+    }
+
+    #[test]
+    fn test_encode_lsb_first() {
+        let mut buffer = [0; 2];
+        encode_slices_with_bit_order(&[0xff], &mut buffer, 8, BitOrder::LsbFirst).unwrap();
+        assert_eq!(&buffer, b"98");
+        encode_slices_with_bit_order(&[0x21], &mut buffer, 8, BitOrder::LsbFirst).unwrap();
+        assert_eq!(&buffer, b"bb");
+        encode_slices_with_bit_order(&[0x01], &mut buffer[..1], 1, BitOrder::LsbFirst).unwrap();
+        assert_eq!(&buffer[..1], b"b");
+        assert!(
+            encode_slices_with_bit_order(&[0x80], &mut buffer[..1], 1, BitOrder::LsbFirst).is_err()
+        );
+        assert!(is_last_octet_valid_with_bit_order(
+            1,
+            0x01,
+            BitOrder::LsbFirst
+        ));
+        assert!(!is_last_octet_valid_with_bit_order(
+            1,
+            0x80,
+            BitOrder::LsbFirst
+        ));
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            // Reference implementation which reads the bits one at a time.
+            let input: Vec<u8> = test.unencoded.iter().map(|x| x.reverse_bits()).collect();
+            let expected: Vec<u8> = (0..test.encoded.len() as u64)
+                .map(|index| {
+                    let mut quintet = 0;
+                    for count in 0..5 {
+                        let bit = index * 5 + count;
+                        if bit < test.bits && input[(bit / 8) as usize] >> (bit % 8) & 1 == 1 {
+                            quintet |= 1 << count;
+                        }
+                    }
+                    quintet
+                })
+                .collect();
+
+            let mut quintets = vec![0; test.encoded.len()];
+            octets_to_quintets_with_bit_order(&input, &mut quintets, test.bits, BitOrder::LsbFirst)
+                .unwrap();
+            assert_eq!(quintets, expected);
+
+            let mut characters = vec![0; test.encoded.len()];
+            encode_slices_with_bit_order(&input, &mut characters, test.bits, BitOrder::LsbFirst)
+                .unwrap();
+            let expected: Vec<u8> = expected
+                .iter()
+                .map(|&x| QUINTET_TO_CHARACTER[x as usize])
+                .collect();
+            assert_eq!(characters, expected);
+        }
+    }
+
+    #[test]
+    fn test_encode_bit_range() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            for &start_bit in &[0, 1, 3, 8, 13] {
+                // Shift the test data right by start_bit bits and surround it with
+                // set bits which must be ignored.
+                let mut input = vec![0xff; test.unencoded.len() + 3];
+                for bit in 0..test.bits {
+                    let set = test.unencoded[(bit / 8) as usize] & (0x80 >> (bit % 8)) != 0;
+                    let out_bit = start_bit + bit;
+                    let mask = 0x80 >> (out_bit % 8);
+                    if set {
+                        input[(out_bit / 8) as usize] |= mask;
+                    } else {
+                        input[(out_bit / 8) as usize] &= !mask;
+                    }
+                }
+
+                let mut buffer = vec![0; test.encoded.len()];
+                encode_bit_range(&input, start_bit, test.bits, &mut buffer).unwrap();
+                assert_eq!(&buffer[..], test.encoded.as_bytes());
+            }
+        }
+
+        let mut buffer = vec![0; 2];
+        assert!(encode_bit_range(&[0, 0], 7, 10, &mut buffer).is_err());
+        assert!(encode_bit_range(&[0, 0], u64::MAX, 10, &mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_encode_in_place() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = test.unencoded.to_vec();
+            buffer.resize(test.encoded.len() + 3, 0);
+            let encoded = encode_in_place(&mut buffer, test.unencoded.len(), test.bits).unwrap();
+            assert_eq!(encoded, test.encoded.as_bytes());
+        }
+
+        let mut buffer = vec![0x01, 0, 0];
+        assert!(encode_in_place(&mut buffer, 1, 1).is_err());
+        assert_eq!(buffer, &[0x01, 0, 0]);
+        let mut buffer = vec![0xff, 0xff, 0];
+        assert!(encode_in_place(&mut buffer, 2, 16).is_err());
+        assert!(encode_in_place(&mut buffer, 2, 8).is_err());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_encode_slices() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = vec![0; test.encoded.len()];
+            par_encode_slices(test.unencoded, &mut buffer, test.bits).unwrap();
+            assert_eq!(&buffer[..], test.encoded.as_bytes());
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_par_encode_slices_multiple_chunks() {
+        let input: Vec<u8> = (0..100_003u32).map(|x| (x * 7 + x / 13) as u8).collect();
+        for &bits in &[800_000, 800_020, 800_024] {
+            let mut octets = input[..required_octets_buffer_len(bits).unwrap()].to_vec();
+            if bits % 8 != 0 {
+                *octets.last_mut().unwrap() &= 0xff << (8 - bits % 8);
+            }
+            let quintets = required_quintets_buffer_len(bits).unwrap();
+            let mut expected = vec![0; quintets];
+            encode_slices(&octets, &mut expected, bits).unwrap();
+            let mut actual = vec![0; quintets];
+            par_encode_slices(&octets, &mut actual, bits).unwrap();
+            assert_eq!(actual, expected);
+        }
+
+        // Non-zero trailing bits in the final chunk clear the whole output
+        let mut octets = input[..100_001].to_vec();
+        octets[100_000] = 0x01;
+        let mut output = vec![b'!'; required_quintets_buffer_len(800_004).unwrap()];
+        assert!(par_encode_slices(&octets, &mut output, 800_004).is_err());
+        assert!(output.iter().all(|&x| x == 0));
+    }
+
+    #[test]
+    fn test_encode_iter() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let encoded: Result<String, ZBase32Error> =
+                encode_iter(test.unencoded.iter().copied(), test.bits).collect();
+            assert_eq!(encoded.unwrap(), test.encoded);
+        }
+
+        assert_eq!(encode_iter(Vec::new(), 0).count(), 0);
+
+        // Too few, too many, and non-zero trailing bits
+        for (octets, bits) in [
+            (&[0u8, 44][..], 25),
+            (&[0, 44, 55, 128, 0], 25),
+            (&[0, 44, 55, 129], 25),
+            (&[0], 0),
+        ]
+        .iter()
+        {
+            let results: Vec<_> = encode_iter(octets.iter().copied(), *bits).collect();
+            assert!(results.last().unwrap().is_err());
+            assert_eq!(results.iter().filter(|x| x.is_err()).count(), 1);
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_encode_zeroizing() {
+        use super::encode_zeroizing;
+
+        let encoded = encode_zeroizing(&[0, 44, 55, 128], 25).unwrap();
+        assert_eq!(encoded.as_str(), "yysdx");
+        assert_eq!(encoded.capacity(), 5);
+
+        assert!(encode_zeroizing(&[0, 44, 55, 129], 25).is_err());
+        assert!(encode_zeroizing(&[0], 1 << 60).is_err());
+    }
+
+    #[test]
+    fn test_encode_error_restores_output() {
+        let mut encoded = String::from("abc");
+        // Non-zero trailing bits in the last octet
+        assert!(encode(&[0, 44, 55, 129], &mut encoded, 25).is_err());
+        assert_eq!(encoded, "abc");
+        assert!(encode_with_case(&[0, 44, 55, 129], &mut encoded, 25, Case::Upper).is_err());
+        assert_eq!(encoded, "abc");
+
+        encode(&[0, 44, 55, 128], &mut encoded, 25).unwrap();
+        assert_eq!(encoded, "abcyysdx");
+    }
+
+    #[test]
+    fn test_encode_slices_error_clears_output() {
+        use super::encode_slices;
+
+        let mut characters = [b'!'; 5];
+        // Non-zero trailing bits are only found after the other characters
+        // have been written
+        assert!(encode_slices(&[0, 44, 55, 129], &mut characters, 25).is_err());
+        assert_eq!(characters, [0; 5]);
+        characters = [b'!'; 5];
+        assert!(
+            encode_slices_with_case(&[0, 44, 55, 129], &mut characters, 25, Case::Upper).is_err()
+        );
+        assert_eq!(characters, [0; 5]);
+
+        encode_slices(&[0, 44, 55, 128], &mut characters, 25).unwrap();
+        assert_eq!(&characters, b"yysdx");
+    }
+
+    #[test]
+    fn test_encode_uninit() {
+        use super::encode_uninit;
+        use core::mem::MaybeUninit;
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = vec![MaybeUninit::uninit(); test.encoded.len()];
+            let encoded = encode_uninit(test.unencoded, &mut buffer, test.bits).unwrap();
+            assert_eq!(encoded, test.encoded.as_bytes());
+        }
+
+        let mut buffer = [MaybeUninit::uninit(); 5];
+        assert!(encode_uninit(&[0, 44, 55, 129], &mut buffer, 25).is_err());
+        assert!(encode_uninit(&[0, 44, 55, 128], &mut buffer[..4], 25).is_err());
+    }
+
+    #[test]
+    fn test_encode_to_vec_and_string() {
+        use super::{encode_to_string, encode_to_vec};
+
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut buffer = b"x".to_vec();
+            encode_to_vec(test.unencoded, &mut buffer, test.bits).unwrap();
+            assert_eq!(&buffer[1..], test.encoded.as_bytes());
+            assert_eq!(
+                encode_to_string(test.unencoded, test.bits).unwrap(),
+                test.encoded
+            );
+        }
+
+        let mut buffer = b"x".to_vec();
+        assert!(encode_to_vec(&[0, 44, 55, 129], &mut buffer, 25).is_err());
+        assert_eq!(buffer, b"x");
+        assert!(encode_to_string(&[0, 44, 55], 25).is_err());
+    }
 }
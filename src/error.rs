@@ -4,6 +4,11 @@ enum InputErrorType {
     InvalidCharacter,
     InvalidQuintet,
     TrailingNonZeroBits,
+    ValueOutOfRange,
+    InvalidLength,
+    InvalidFormat,
+    NonCanonicalCase,
+    InvalidChecksum,
 }
 
 pub struct InputErrorCause {
@@ -18,6 +23,18 @@ impl Debug for InputErrorCause {
             InputErrorType::TrailingNonZeroBits => {
                 write!(f, "Trailing non-zero bits found in input.")
             }
+            InputErrorType::ValueOutOfRange => {
+                write!(
+                    f,
+                    "Input value doesn't fit in the specified number of bits."
+                )
+            }
+            InputErrorType::InvalidLength => write!(f, "Input has an invalid length."),
+            InputErrorType::InvalidFormat => write!(f, "Input isn't in the expected format."),
+            InputErrorType::NonCanonicalCase => {
+                write!(f, "Upper-case character found in input.")
+            }
+            InputErrorType::InvalidChecksum => write!(f, "Input has an invalid checksum."),
         }
     }
 }
@@ -32,6 +49,9 @@ enum UsageErrorType {
     InputBufferDoesntMatchBits,
     OutputBufferDoesntMatchBits,
     BitsOverflow,
+    BitRangeOutOfBounds,
+    BitsExceedIntegerWidth,
+    InvalidPrefix,
 }
 
 pub struct UsageErrorCause {
@@ -56,6 +76,18 @@ impl Debug for UsageErrorCause {
             UsageErrorType::BitsOverflow => {
                 write!(f, "The value for bits was too large for the platform usize")
             }
+            UsageErrorType::BitRangeOutOfBounds => {
+                write!(f, "The bit range extends past the end of the buffer")
+            }
+            UsageErrorType::BitsExceedIntegerWidth => {
+                write!(
+                    f,
+                    "The value for bits was larger than the width of the integer type"
+                )
+            }
+            UsageErrorType::InvalidPrefix => {
+                write!(f, "The human-readable prefix is empty, too long, or contains an invalid character")
+            }
         }
     }
 }
@@ -101,6 +133,11 @@ impl Display for ZBase32Error {
 #[cfg(feature = "std")]
 impl std::error::Error for ZBase32Error {}
 
+// Equivalent to the From implementation below, but usable in const fns.
+pub const fn usage_error(err: UsageError) -> ZBase32Error {
+    ZBase32Error::UsageError(err.0)
+}
+
 impl From<UsageError> for ZBase32Error {
     fn from(err: UsageError) -> Self {
         ZBase32Error::UsageError(err.0)
@@ -147,6 +184,36 @@ pub const fn trailing_nonzero_bits() -> ZBase32Error {
     })
 }
 
+pub const fn value_out_of_range() -> ZBase32Error {
+    ZBase32Error::InputError(InputErrorCause {
+        typ: InputErrorType::ValueOutOfRange,
+    })
+}
+
+pub const fn invalid_length() -> ZBase32Error {
+    ZBase32Error::InputError(InputErrorCause {
+        typ: InputErrorType::InvalidLength,
+    })
+}
+
+pub const fn invalid_format() -> ZBase32Error {
+    ZBase32Error::InputError(InputErrorCause {
+        typ: InputErrorType::InvalidFormat,
+    })
+}
+
+pub const fn non_canonical_case() -> ZBase32Error {
+    ZBase32Error::InputError(InputErrorCause {
+        typ: InputErrorType::NonCanonicalCase,
+    })
+}
+
+pub const fn invalid_checksum() -> ZBase32Error {
+    ZBase32Error::InputError(InputErrorCause {
+        typ: InputErrorType::InvalidChecksum,
+    })
+}
+
 pub const fn input_buffer_doesnt_match_bits() -> UsageError {
     UsageError(UsageErrorCause {
         typ: UsageErrorType::InputBufferDoesntMatchBits,
@@ -164,3 +231,21 @@ pub const fn bits_overflow() -> UsageError {
         typ: UsageErrorType::BitsOverflow,
     })
 }
+
+pub const fn bit_range_out_of_bounds() -> UsageError {
+    UsageError(UsageErrorCause {
+        typ: UsageErrorType::BitRangeOutOfBounds,
+    })
+}
+
+pub const fn bits_exceed_integer_width() -> UsageError {
+    UsageError(UsageErrorCause {
+        typ: UsageErrorType::BitsExceedIntegerWidth,
+    })
+}
+
+pub const fn invalid_prefix() -> UsageError {
+    UsageError(UsageErrorCause {
+        typ: UsageErrorType::InvalidPrefix,
+    })
+}
@@ -0,0 +1,314 @@
+#[cfg(feature = "std")]
+use crate::decode_impl::decode_uninit;
+use crate::decode_impl::{character_to_quintet, decode_slices};
+use crate::encode_impl::encode_slices;
+#[cfg(feature = "std")]
+use crate::encode_impl::encode_uninit;
+#[cfg(feature = "std")]
+use crate::error::input_buffer_doesnt_match_bits;
+use crate::error::{
+    bits_overflow, invalid_format, invalid_length, output_buffer_doesnt_match_bits,
+};
+use crate::tables::QUINTET_TO_CHARACTER;
+#[cfg(feature = "std")]
+use crate::util::required_octets_buffer_len;
+use crate::util::required_quintets_buffer_len;
+use crate::{UsageError, ZBase32Error};
+
+/// Each header quintet carries 4 bits of the length.
+const HEADER_GROUP_BITS: u32 = 4;
+
+/// Set on every header quintet except for the last one.
+const HEADER_CONTINUATION_FLAG: u8 = 0x10;
+
+/// Calculate the number of characters in the header of a framed value of `bits` bits.
+pub const fn framed_header_len(bits: u64) -> usize {
+    let significant_bits = u64::BITS - bits.leading_zeros();
+    match significant_bits {
+        0 => 1,
+        x => ((x - 1) / HEADER_GROUP_BITS + 1) as usize,
+    }
+}
+
+/// Calculate the number of characters that are required to hold a framed value
+/// of the specified number of bits, including its header.
+///
+/// This function will return an Err value if the specified number of bits
+/// would result in needing more than [`usize::MAX`] characters.
+pub fn required_framed_buffer_len(bits: u64) -> Result<usize, UsageError> {
+    required_quintets_buffer_len(bits)?
+        .checked_add(framed_header_len(bits))
+        .ok_or_else(bits_overflow)
+}
+
+/// Produce the header characters of a framed value of `bits` bits.
+fn header_characters(bits: u64) -> impl Iterator<Item = u8> {
+    let last_group = framed_header_len(bits) - 1;
+    (0..=last_group).map(move |index| {
+        let remaining_groups = (last_group - index) as u32;
+        let group = (bits >> (remaining_groups * HEADER_GROUP_BITS)) as u8 & 0x0f;
+        let flag = if remaining_groups == 0 {
+            0
+        } else {
+            HEADER_CONTINUATION_FLAG
+        };
+        QUINTET_TO_CHARACTER[(group | flag) as usize]
+    })
+}
+
+/// Encode a buffer of octets (bytes) to a buffer of characters, prefixed with
+/// a header that records the number of bits.
+///
+/// The header stores `bits` in groups of 4 bits, starting from the most significant
+/// non-zero group, with one character per group. Every header character except
+/// for the last one has its highest bit set. The encoded value follows the header
+/// and is the same as the output of [`encode_slices`].
+///
+/// The length of `in_octets` must match the value returned by
+/// [`required_octets_buffer_len`](crate::low_level_decode::required_octets_buffer_len)
+/// and the length of `out_characters` must match the value returned by
+/// [`required_framed_buffer_len`].
+pub fn encode_framed_to_slice(
+    in_octets: &[u8],
+    out_characters: &mut [u8],
+    bits: u64,
+) -> Result<(), ZBase32Error> {
+    if out_characters.len() != required_framed_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+    let (header, body) = out_characters.split_at_mut(framed_header_len(bits));
+    for (out, character) in header.iter_mut().zip(header_characters(bits)) {
+        *out = character;
+    }
+    encode_slices(in_octets, body, bits)
+}
+
+/// Decode the header of a framed value, returning the number of bits and the
+/// number of characters in the header.
+///
+/// An InputError is returned if the header is truncated, not in its shortest form,
+/// contains invalid characters, or declares more than `max_bits` bits. The characters
+/// following the header aren't checked.
+pub fn decode_framed_header(
+    in_characters: &[u8],
+    max_bits: u64,
+) -> Result<(u64, usize), ZBase32Error> {
+    let mut bits: u64 = 0;
+    for (index, &character) in in_characters.iter().enumerate() {
+        let quintet = character_to_quintet(character)?;
+        let group = (quintet & 0x0f) as u64;
+        if index == 0 && group == 0 && quintet & HEADER_CONTINUATION_FLAG != 0 {
+            return Err(invalid_format());
+        }
+        if bits.leading_zeros() < HEADER_GROUP_BITS {
+            return Err(invalid_format());
+        }
+        bits = (bits << HEADER_GROUP_BITS) | group;
+        if bits > max_bits {
+            return Err(invalid_length());
+        }
+        if quintet & HEADER_CONTINUATION_FLAG == 0 {
+            return Ok((bits, index + 1));
+        }
+    }
+    Err(invalid_format())
+}
+
+/// Decode a framed buffer of characters into a buffer of octets (bytes),
+/// returning the number of bits.
+///
+/// The length of `out_octets` must match the value returned by
+/// [`required_octets_buffer_len`](crate::low_level_decode::required_octets_buffer_len)
+/// for the number of bits recorded in the header, which may be read with
+/// [`decode_framed_header`].
+///
+/// An InputError is returned if the header declares more than `max_bits` bits
+/// or if the number of characters following the header doesn't agree with it.
+pub fn decode_framed_to_slice(
+    in_characters: &[u8],
+    out_octets: &mut [u8],
+    max_bits: u64,
+) -> Result<u64, ZBase32Error> {
+    let (bits, header_len) = decode_framed_header(in_characters, max_bits)?;
+    let body = &in_characters[header_len..];
+    if body.len() != required_quintets_buffer_len(bits)? {
+        return Err(invalid_length());
+    }
+    decode_slices(body, out_octets, bits)?;
+    Ok(bits)
+}
+
+/// Encode a slice of octets (bytes) to a [`String`], prefixed with a header
+/// that records the number of bits.
+///
+/// The output characters will be appended to `output`. See
+/// [`encode_framed_to_slice`] for a description of the format. If an error is
+/// returned, `output` is restored to its original length.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_framed(input: &[u8], output: &mut String, bits: u64) -> Result<(), ZBase32Error> {
+    if input.len() != required_octets_buffer_len(bits)? {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    let needed_characters = required_framed_buffer_len(bits)?;
+    // SAFETY: only ASCII characters are written to the Vec, so the String
+    // always holds valid UTF-8.
+    let output = unsafe { output.as_mut_vec() };
+    output.reserve(needed_characters);
+    let start = output.len();
+
+    let (header, body) =
+        output.spare_capacity_mut()[..needed_characters].split_at_mut(framed_header_len(bits));
+    encode_uninit(input, body, bits)?;
+    for (out, character) in header.iter_mut().zip(header_characters(bits)) {
+        out.write(character);
+    }
+    // SAFETY: the header and encode_uninit together initialized the first
+    // `needed_characters` elements of the spare capacity.
+    unsafe { output.set_len(start + needed_characters) };
+    Ok(())
+}
+
+/// Decode a framed value to a [`Vec`] of octets (bytes), returning the number of bits.
+///
+/// The output octets will be appended to `output`. An InputError is returned if the
+/// header declares more than `max_bits` bits or if the number of characters following
+/// the header doesn't agree with it, in which case nothing is allocated. If an error
+/// is returned, `output` is restored to its original length.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn decode_framed(
+    input: &str,
+    output: &mut Vec<u8>,
+    max_bits: u64,
+) -> Result<u64, ZBase32Error> {
+    let (bits, header_len) = decode_framed_header(input.as_bytes(), max_bits)?;
+    let body = &input.as_bytes()[header_len..];
+    // The header is untrusted, so it is checked against the length of the body
+    // before any space is reserved for the output.
+    if body.len() != required_quintets_buffer_len(bits)? {
+        return Err(invalid_length());
+    }
+    let needed_octets = required_octets_buffer_len(bits)?;
+    output.reserve(needed_octets);
+    let start = output.len();
+
+    // The octets are decoded directly into the spare capacity, so the length of
+    // `output` is only changed once decoding has succeeded.
+    decode_uninit(
+        body,
+        &mut output.spare_capacity_mut()[..needed_octets],
+        bits,
+    )?;
+    // SAFETY: decode_uninit initialized the first `needed_octets` elements of
+    // the spare capacity.
+    unsafe { output.set_len(start + needed_octets) };
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+
+    #[test]
+    fn test_framed_header_len() {
+        assert_eq!(framed_header_len(0), 1);
+        assert_eq!(framed_header_len(15), 1);
+        assert_eq!(framed_header_len(16), 2);
+        assert_eq!(framed_header_len(255), 2);
+        assert_eq!(framed_header_len(256), 3);
+        assert_eq!(framed_header_len(u64::MAX), 16);
+    }
+
+    #[test]
+    fn test_encode_framed() {
+        let mut encoded = String::new();
+        encode_framed(&[0, 44, 55, 128], &mut encoded, 25).unwrap();
+        assert_eq!(encoded, "tjyysdx");
+
+        let mut encoded = String::new();
+        encode_framed(&[], &mut encoded, 0).unwrap();
+        assert_eq!(encoded, "y");
+
+        // Non-zero trailing bits
+        let mut encoded = String::from("x");
+        assert!(encode_framed(&[0, 44, 55, 129], &mut encoded, 25).is_err());
+        assert_eq!(encoded, "x");
+
+        let mut buffer = [0u8; 7];
+        encode_framed_to_slice(&[0, 44, 55, 128], &mut buffer, 25).unwrap();
+        assert_eq!(&buffer, b"tjyysdx");
+    }
+
+    #[test]
+    fn test_framed_round_trip() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let mut encoded = String::new();
+            encode_framed(test.unencoded, &mut encoded, test.bits).unwrap();
+            assert!(encoded.ends_with(test.encoded));
+
+            let mut decoded = Vec::new();
+            let bits = decode_framed(&encoded, &mut decoded, test.bits).unwrap();
+            assert_eq!(bits, test.bits);
+            assert_eq!(decoded, test.unencoded);
+        }
+    }
+
+    #[test]
+    fn test_decode_framed_errors() {
+        let mut decoded = Vec::new();
+        // Declared length exceeds max_bits
+        assert!(matches!(
+            decode_framed("tjyysdx", &mut decoded, 24),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Body doesn't match the declared length
+        assert!(matches!(
+            decode_framed("tjyysd", &mut decoded, 25),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Truncated header
+        assert!(matches!(
+            decode_framed("t", &mut decoded, 25),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Header not in its shortest form
+        assert!(matches!(
+            decode_framed("otjyysdx", &mut decoded, 25),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Header overflows a u64
+        let mut header = "9".repeat(17);
+        header.push('y');
+        assert!(matches!(
+            decode_framed(&header, &mut decoded, u64::MAX),
+            Err(ZBase32Error::InputError(_))
+        ));
+        assert!(decoded.is_empty());
+
+        // Invalid character in the body
+        let mut decoded = vec![1];
+        assert!(decode_framed("tjyysd!", &mut decoded, 25).is_err());
+        assert_eq!(decoded, [1]);
+    }
+
+    #[test]
+    fn test_framed_untrusted_lengths() {
+        // A header declaring 2^50 bits followed by a short body
+        let input = format!("w{}yyy", "o".repeat(11));
+        assert_eq!(
+            decode_framed_header(input.as_bytes(), u64::MAX).unwrap().0,
+            1 << 50
+        );
+        let mut output = Vec::new();
+        assert!(decode_framed(&input, &mut output, u64::MAX).is_err());
+        assert!(output.is_empty());
+
+        let mut output = String::new();
+        assert!(encode_framed(&[0], &mut output, 1 << 60).is_err());
+        assert!(output.is_empty());
+    }
+}
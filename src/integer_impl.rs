@@ -0,0 +1,324 @@
+use crate::decode_impl::character_to_quintet;
+use crate::error::{
+    bits_exceed_integer_width, input_buffer_doesnt_match_bits, output_buffer_doesnt_match_bits,
+    trailing_nonzero_bits, usage_error, value_out_of_range,
+};
+use crate::tables::QUINTET_TO_CHARACTER;
+#[cfg(feature = "std")]
+use crate::util::required_quintets_buffer_len;
+use crate::ZBase32Error;
+
+// All of the integer types are handled by widening them to a u128. `width`
+// is the number of bits in the original integer type.
+//
+// The functions are const fns, so they work directly on the quintets of the
+// value rather than going through the slice-based functions.
+
+const fn encode_integer(
+    value: u128,
+    width: u32,
+    bits: u64,
+    out_characters: &mut [u8],
+) -> Result<(), ZBase32Error> {
+    if bits > width as u64 {
+        return Err(usage_error(bits_exceed_integer_width()));
+    }
+    if bits < 128 && value >> bits != 0 {
+        return Err(value_out_of_range());
+    }
+    if out_characters.len() as u64 != bits.div_ceil(5) {
+        return Err(usage_error(output_buffer_doesnt_match_bits()));
+    }
+
+    // Quintet `index` holds the 5 bits that start `index * 5` bits below the
+    // highest of the `bits` bits. The last quintet is padded with zero bits.
+    let mut index = 0;
+    while index < out_characters.len() {
+        let shift = bits as i64 - (index as i64 + 1) * 5;
+        let quintet = if shift >= 0 {
+            value >> shift
+        } else {
+            value << -shift
+        };
+        out_characters[index] = QUINTET_TO_CHARACTER[(quintet & 0x1f) as usize];
+        index += 1;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+fn encode_integer_to_string(value: u128, width: u32, bits: u64) -> Result<String, ZBase32Error> {
+    let mut characters = [0u8; 26];
+    let characters = characters
+        .get_mut(..required_quintets_buffer_len(bits)?)
+        .ok_or_else(bits_exceed_integer_width)?;
+    encode_integer(value, width, bits, characters)?;
+    Ok(characters.iter().map(|&x| x as char).collect())
+}
+
+const fn decode_integer(input: &str, width: u32, bits: u64) -> Result<u128, ZBase32Error> {
+    if bits > width as u64 {
+        return Err(usage_error(bits_exceed_integer_width()));
+    }
+    let input = input.as_bytes();
+    if input.len() as u64 != bits.div_ceil(5) {
+        return Err(usage_error(input_buffer_doesnt_match_bits()));
+    }
+
+    let mut value = 0u128;
+    let mut index = 0;
+    while index < input.len() {
+        let quintet = match character_to_quintet(input[index]) {
+            Ok(quintet) => quintet,
+            Err(err) => return Err(err),
+        };
+        if index + 1 < input.len() {
+            value = value << 5 | quintet as u128;
+        } else {
+            // Only the highest bits of the last quintet are part of the value
+            let last_quintet_bits = (bits - index as u64 * 5) as u32;
+            if quintet & (0x1f >> last_quintet_bits) != 0 {
+                return Err(trailing_nonzero_bits());
+            }
+            value = value << last_quintet_bits | (quintet >> (5 - last_quintet_bits)) as u128;
+        }
+        index += 1;
+    }
+    Ok(value)
+}
+
+// The minimal encodings use the smallest number of characters that can hold
+// the significant bits of the value, and then use all of the bits of those
+// characters (up to the width of the integer type). This way, the number of
+// bits can be recovered from the length of the encoded value when decoding.
+
+#[cfg(feature = "std")]
+fn minimal_bits(value: u128, width: u32) -> u64 {
+    let significant_bits = (128 - value.leading_zeros()).max(1) as u64;
+    let remainder = significant_bits % 5;
+    let characters = significant_bits / 5 + if remainder == 0 { 0 } else { 1 };
+    (characters * 5).min(width as u64)
+}
+
+const fn minimal_bits_for_input(input: &str, width: u32) -> u64 {
+    let bits = (input.len() as u64).saturating_mul(5);
+    if bits < width as u64 {
+        bits
+    } else {
+        width as u64
+    }
+}
+
+macro_rules! integer_functions {
+    (
+        $typ:ident,
+        $encode_to_slice:ident,
+        $encode:ident,
+        $decode:ident,
+        $encode_minimal:ident,
+        $decode_minimal:ident
+    ) => {
+        #[doc = concat!("Encode a [`", stringify!($typ), "`] value to a buffer of characters ")]
+        #[doc = "using `bits` bits."]
+        ///
+        /// This method is the equivalent of
+        #[doc = concat!("[`", stringify!($encode), "`]")]
+        /// which does not allocate and is usable in `no_std` mode. The length of
+        /// `out_characters` must match the value returned by
+        /// [`required_quintets_buffer_len`](crate::low_level_encode::required_quintets_buffer_len).
+        pub const fn $encode_to_slice(
+            value: $typ,
+            bits: u64,
+            out_characters: &mut [u8],
+        ) -> Result<(), ZBase32Error> {
+            encode_integer(value as u128, $typ::BITS, bits, out_characters)
+        }
+
+        #[doc = concat!("Encode a [`", stringify!($typ), "`] value as a [`String`] of ")]
+        #[doc = "z-base-32 characters using `bits` bits."]
+        ///
+        /// The value is right-aligned into the `bits` bits - so, for example, encoding
+        /// the value `1` using 5 bits produces `"b"`. An Err value is returned if the
+        /// value doesn't fit in `bits` bits or if `bits` is larger than the width
+        /// of the integer type.
+        ///
+        /// This method is not available in `no_std` mode.
+        #[cfg(feature = "std")]
+        pub fn $encode(value: $typ, bits: u64) -> Result<String, ZBase32Error> {
+            encode_integer_to_string(value as u128, $typ::BITS, bits)
+        }
+
+        #[doc = concat!("Decode a [`", stringify!($typ), "`] value that was encoded using `bits` bits.")]
+        ///
+        /// This is the reverse of
+        #[doc = concat!("[`", stringify!($encode), "`].")]
+        /// The length of `input` must match the value returned by
+        /// [`required_quintets_buffer_len`](crate::low_level_encode::required_quintets_buffer_len).
+        pub const fn $decode(input: &str, bits: u64) -> Result<$typ, ZBase32Error> {
+            match decode_integer(input, $typ::BITS, bits) {
+                Ok(value) => Ok(value as $typ),
+                Err(err) => Err(err),
+            }
+        }
+
+        #[doc = concat!("Encode a [`", stringify!($typ), "`] value as a [`String`] using the")]
+        /// smallest number of characters that can hold it.
+        ///
+        /// All of the bits of the output characters are used (up to the width of the integer
+        /// type) so that the number of bits doesn't have to be agreed upon separately -
+        /// the encoded value can be decoded with
+        #[doc = concat!("[`", stringify!($decode_minimal), "`].")]
+        /// The value `0` is encoded as a single character.
+        ///
+        /// This method is not available in `no_std` mode.
+        #[cfg(feature = "std")]
+        pub fn $encode_minimal(value: $typ) -> String {
+            let bits = minimal_bits(value as u128, $typ::BITS);
+            encode_integer_to_string(value as u128, $typ::BITS, bits)
+                .expect("A minimal width always holds the value")
+        }
+
+        #[doc = concat!("Decode a [`", stringify!($typ), "`] value that was encoded by")]
+        #[doc = concat!("[`", stringify!($encode_minimal), "`].")]
+        ///
+        /// The number of bits is determined from the length of `input`: 5 bits per
+        /// character, up to the width of the integer type. Leading `y` (zero)
+        /// characters are accepted, but `input` is rejected if it is longer than the
+        #[doc = concat!("encoding of [`", stringify!($typ), "::MAX`], even if the value it holds would fit.")]
+        pub const fn $decode_minimal(input: &str) -> Result<$typ, ZBase32Error> {
+            let bits = minimal_bits_for_input(input, $typ::BITS);
+            $decode(input, bits)
+        }
+    };
+}
+
+integer_functions!(
+    u16,
+    encode_u16_to_slice,
+    encode_u16,
+    decode_u16,
+    encode_u16_minimal,
+    decode_u16_minimal
+);
+integer_functions!(
+    u32,
+    encode_u32_to_slice,
+    encode_u32,
+    decode_u32,
+    encode_u32_minimal,
+    decode_u32_minimal
+);
+integer_functions!(
+    u64,
+    encode_u64_to_slice,
+    encode_u64,
+    decode_u64,
+    encode_u64_minimal,
+    decode_u64_minimal
+);
+integer_functions!(
+    u128,
+    encode_u128_to_slice,
+    encode_u128,
+    decode_u128,
+    encode_u128_minimal,
+    decode_u128_minimal
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_fixed_width() {
+        assert_eq!(encode_u64(1, 5).unwrap(), "b");
+        assert_eq!(encode_u64(1, 1).unwrap(), "o");
+        assert_eq!(encode_u64(0, 0).unwrap(), "");
+        assert_eq!(encode_u16(0xffff, 16).unwrap(), "999o");
+        assert_eq!(decode_u16("999o", 16).unwrap(), 0xffff);
+        assert_eq!(decode_u64("b", 5).unwrap(), 1);
+        assert_eq!(decode_u64("o", 1).unwrap(), 1);
+        assert_eq!(decode_u64("", 0).unwrap(), 0);
+
+        for &(value, bits) in &[
+            (0u128, 1u64),
+            (1, 128),
+            (u128::MAX, 128),
+            (0x1234_5678_9abc_def0, 61),
+            (u64::MAX as u128, 64),
+        ] {
+            let encoded = encode_u128(value, bits).unwrap();
+            assert_eq!(decode_u128(&encoded, bits).unwrap(), value);
+        }
+        let encoded = encode_u32(0xdead_beef, 32).unwrap();
+        assert_eq!(decode_u32(&encoded, 32).unwrap(), 0xdead_beef);
+    }
+
+    #[test]
+    fn test_const_evaluation() {
+        const ENCODED: [u8; 4] = {
+            let mut characters = [0u8; 4];
+            match encode_u16_to_slice(0xffff, 16, &mut characters) {
+                Ok(()) => characters,
+                Err(_) => panic!(),
+            }
+        };
+        const DECODED: u128 = match decode_u128_minimal("999o") {
+            Ok(value) => value,
+            Err(_) => panic!(),
+        };
+        assert_eq!(&ENCODED, b"999o");
+        assert_eq!(DECODED, 0xffff0);
+    }
+
+    #[test]
+    fn test_encode_decode_errors() {
+        // Value doesn't fit
+        assert!(matches!(encode_u64(2, 1), Err(ZBase32Error::InputError(_))));
+        assert!(matches!(
+            encode_u16(0x8000, 15),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Bits larger than the integer type
+        assert!(matches!(
+            encode_u32(0, 33),
+            Err(ZBase32Error::UsageError(_))
+        ));
+        assert!(matches!(
+            decode_u16("yyyy", 17),
+            Err(ZBase32Error::UsageError(_))
+        ));
+        // Trailing bits
+        assert!(decode_u64("b", 1).is_err());
+    }
+
+    #[test]
+    fn test_encode_to_slice() {
+        let mut buffer = [0u8; 4];
+        encode_u16_to_slice(0xffff, 16, &mut buffer).unwrap();
+        assert_eq!(&buffer, b"999o");
+        assert!(encode_u16_to_slice(0xffff, 15, &mut buffer).is_err());
+        assert!(encode_u16_to_slice(1, 16, &mut buffer[..3]).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_minimal() {
+        assert_eq!(encode_u32_minimal(0), "y");
+        assert_eq!(encode_u32_minimal(31), "9");
+        assert_eq!(encode_u32_minimal(32), "by");
+        assert_eq!(decode_u32_minimal("by").unwrap(), 32);
+        assert_eq!(decode_u32_minimal("yby").unwrap(), 32);
+        assert_eq!(encode_u64_minimal(u64::MAX).len(), 13);
+        assert_eq!(encode_u128_minimal(u128::MAX).len(), 26);
+        assert!(decode_u16_minimal("yyyyy").is_err());
+
+        for &value in &[0u64, 1, 31, 32, 1023, 1024, u32::MAX as u64, u64::MAX] {
+            let encoded = encode_u64_minimal(value);
+            assert_eq!(decode_u64_minimal(&encoded).unwrap(), value);
+        }
+        for &value in &[0u16, 1, 0x7fff, 0x8000, u16::MAX] {
+            let encoded = encode_u16_minimal(value);
+            assert_eq!(decode_u16_minimal(&encoded).unwrap(), value);
+        }
+    }
+}
@@ -35,7 +35,9 @@
 //!
 //! When using either API, its important to remember that z-base-32 is big-endian
 //! oriented. As such, if you encode a single bit, z-base-32 will encode
-//! the _highest bit_ of the input byte.
+//! the _highest bit_ of the input byte. For interoperability with protocols that
+//! pack bits starting from the lowest bit, the low-level API also offers `_with_bit_order`
+//! variants of its conversion functions which accept a [`BitOrder`].
 //!
 //! When encoding or decoding, if the input value includes non-zero bits past
 //! the number of bits specified in the operation, an Error be returned. For example,
@@ -51,7 +53,23 @@
 //! ## High-level API
 //!
 //! The high-level API consists of the functions [`encode`] and
-//! its reverse, [`decode`].
+//! its reverse, [`decode`]. [`encode_with_case`] can be used instead of [`encode`]
+//! to produce upper-case output, and [`decode_strict`] can be used instead of [`decode`]
+//! to only accept the exact lower-case output of [`encode`]. [`encode_to_vec`] and
+//! [`decode_bytes_input`] work with `Vec<u8>` and `&[u8]` values instead of strings,
+//! while [`encode_to_string`] and [`decode_to_vec`] return newly allocated output.
+//!
+//! The high-level functions append their output to a [`String`] or [`Vec`] provided by
+//! the caller. If an error is returned, the caller's buffer is left at its original
+//! length, without any partial output.
+//!
+//! The [`ToZBase32`] and [`FromZBase32`] traits offer the same functionality as
+//! methods on byte containers, for example `key.to_zbase32()` or
+//! `<[u8; 32]>::from_zbase32(text)`.
+//!
+//! Once a value has been validated, it can be held as a [`ZBase32String`], or borrowed as a
+//! [`ZBase32Str`] if it encodes a whole number of bytes, so that it doesn't need to be
+//! validated again when it is decoded.
 //!
 //! Example:
 //!
@@ -96,11 +114,47 @@
 //!   to the functions in the high-level API - but require the caller to setup
 //!   an appropriate output buffer.
 //!
+//! * If `encode_slices` or `decode_slices` (or their `_with_case`, `_with_bit_order`,
+//!   `_strict`, and parallel variants) reject their input, the whole output buffer is
+//!   filled with zeros. The exceptions are the in-place and bit range functions, which
+//!   may leave their output partially written, and the `_uninit` functions, whose
+//!   output must be treated as uninitialized if an error is returned. Since the buffer
+//!   is a slice, its length never changes.
+//!
 //! ## No_std
 //!
 //! No_std mode may be activated by disabling the "std" feature. In this
 //! mode, only the low-level interfaces are available.
 //!
+//! ## Optional features
+//!
+//! * "rayon" - Adds `par_encode_slices` and `par_decode_slices` to the low-level API
+//!   which split large buffers on 40-bit group boundaries and process the chunks
+//!   in parallel.
+//!
+//! * "rand_core" - Adds the `random` module for generating random encoded values,
+//!   such as invitation codes.
+//!
+//! * "uuid" - Adds the `uuid` module for encoding UUIDs as 26 character values
+//!   and generating ULID-style identifiers.
+//!
+//! * "tokio" and "futures-io" - Add the `tokio` and `futures_io` modules with
+//!   `EncoderWriter` and `DecoderReader` adapters which encode and decode
+//!   whole-byte data on the fly as it is written to or read from an async stream.
+//!
+//! * "tokio-util" - Adds the `codec` module with `LineCodec`, which encodes and
+//!   decodes newline-delimited messages.
+//!
+//! * "bytes" - Implements `ToZBase32` for `bytes::Bytes`.
+//!
+//! * "zeroize" - Wipes the internal buffers used while encoding and decoding when
+//!   they are no longer needed, and adds `encode_zeroizing` and `decode_zeroizing`
+//!   to the high-level API, which return their output in a `Zeroizing` wrapper.
+//!   Wiping is best-effort: the encoder and decoder state is passed by value from
+//!   one step to the next, and the copies that these moves may leave behind (for
+//!   example, on the stack) aren't wiped. Only the final location of each state is
+//!   wiped when it is dropped.
+//!
 //! ## License
 //
 //! This project is licensed under either of
@@ -114,36 +168,224 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod bit_order;
+mod case;
+mod checked_impl;
+#[cfg(feature = "tokio-util")]
+mod codec_impl;
+mod constant_time_impl;
 mod decode_impl;
 mod encode_impl;
 mod error;
+mod framed_impl;
+#[cfg(feature = "futures-io")]
+mod futures_io_impl;
+mod integer_impl;
+mod lightning_impl;
+mod pkarr_impl;
+#[cfg(feature = "rand_core")]
+mod random_impl;
 mod stateful_decoder;
 mod stateful_encoder;
+#[cfg(any(feature = "tokio", feature = "futures-io"))]
+mod stream_impl;
+mod string_impl;
 mod tables;
 #[cfg(test)]
 mod test_data;
+#[cfg(feature = "tokio")]
+mod tokio_impl;
+mod traits_impl;
 mod util;
+#[cfg(feature = "uuid")]
+mod uuid_impl;
 
+pub use bit_order::BitOrder;
+pub use case::Case;
 pub use error::{InputErrorCause, UsageError, UsageErrorCause, ZBase32Error};
+pub use string_impl::ZBase32Str;
+pub use traits_impl::FromZBase32;
 
 #[cfg(feature = "std")]
-pub use decode_impl::decode;
+pub use string_impl::ZBase32String;
 
 #[cfg(feature = "std")]
-pub use encode_impl::encode;
+pub use traits_impl::ToZBase32;
+
+#[cfg(feature = "std")]
+pub use decode_impl::{decode, decode_bytes_input, decode_strict, decode_to_vec};
+
+#[cfg(feature = "std")]
+pub use encode_impl::{encode, encode_to_string, encode_to_vec, encode_with_case};
+
+#[cfg(all(feature = "std", feature = "zeroize"))]
+pub use decode_impl::decode_zeroizing;
+
+#[cfg(all(feature = "std", feature = "zeroize"))]
+pub use encode_impl::encode_zeroizing;
+
+pub mod checked {
+    //! Encoding with a human-readable prefix and a bech32m checksum
+    //!
+    //! Checked values look like `inv1<value><checksum>`, where the checksum is computed
+    //! over the quintet values of the encoded value, using the same algorithm as
+    //! [bech32m](https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki).
+    #[cfg(feature = "std")]
+    pub use crate::checked_impl::{decode_checked, encode_checked};
+    pub use crate::checked_impl::{
+        decode_checked_to_slice, encode_checked_to_slice, required_checked_buffer_len,
+        CHECKSUM_LEN, MAX_PREFIX_LEN, SEPARATOR,
+    };
+}
+
+pub mod constant_time {
+    //! Encoding and decoding of secrets, such as private keys, without
+    //! secret-dependent branches or table lookups
+    //!
+    //! The regular functions map between characters and quintets using lookup tables
+    //! and stop at the first invalid character, so the time they take and the memory
+    //! they access can reveal information about the value being processed. The
+    //! functions in this module instead compare each value against the whole alphabet
+    //! using arithmetic masks and only report errors once all of the input has been
+    //! processed. They are slower than the regular functions and should only be used
+    //! when that matters.
+    //!
+    //! These functions are written to avoid secret-dependent timing, but since the
+    //! compiler is free to transform them, this is a best-effort guarantee.
+    #[cfg(feature = "std")]
+    pub use crate::constant_time_impl::{decode_ct, encode_ct};
+    pub use crate::constant_time_impl::{decode_slices_ct, encode_slices_ct, eq_ct};
+}
+
+#[cfg(feature = "tokio-util")]
+pub mod codec {
+    //! A [tokio-util](https://docs.rs/tokio-util) codec for messages that are
+    //! encoded as z-base-32 and separated by newlines
+    //!
+    //! This module is only available if the "tokio-util" feature is enabled.
+    pub use crate::codec_impl::{CodecError, LineCodec};
+}
+
+pub mod framed {
+    //! Self-describing encoding in which the encoded value is prefixed with its
+    //! length in bits
+    //!
+    //! Framed values don't require the encoding and decoding parties to agree on
+    //! the number of bits ahead of time. Instead, the decoder is given an upper bound
+    //! on the number of bits it will accept.
+    #[cfg(feature = "std")]
+    pub use crate::framed_impl::{decode_framed, encode_framed};
+    pub use crate::framed_impl::{
+        decode_framed_header, decode_framed_to_slice, encode_framed_to_slice, framed_header_len,
+        required_framed_buffer_len,
+    };
+}
+
+#[cfg(feature = "futures-io")]
+pub mod futures_io {
+    //! Adapters that encode and decode data written to or read from
+    //! [futures-io](https://docs.rs/futures-io) streams
+    //!
+    //! This module is only available if the "futures-io" feature is enabled.
+    pub use crate::futures_io_impl::{DecoderReader, EncoderWriter};
+}
+
+pub mod integer {
+    //! Encoding and decoding of unsigned integer values
+    //!
+    //! The `encode_*` and `decode_*` functions right-align an integer into a specified
+    //! number of bits, while the `*_minimal` variants use the smallest number of
+    //! characters that can hold the value.
+    //!
+    //! The functions that don't allocate are `const fn`s, so they can be used to
+    //! compute encoded or decoded constants at compile time.
+    pub use crate::integer_impl::{
+        decode_u128, decode_u128_minimal, decode_u16, decode_u16_minimal, decode_u32,
+        decode_u32_minimal, decode_u64, decode_u64_minimal, encode_u128_to_slice,
+        encode_u16_to_slice, encode_u32_to_slice, encode_u64_to_slice,
+    };
+    #[cfg(feature = "std")]
+    pub use crate::integer_impl::{
+        encode_u128, encode_u128_minimal, encode_u16, encode_u16_minimal, encode_u32,
+        encode_u32_minimal, encode_u64, encode_u64_minimal,
+    };
+}
+
+pub mod lightning {
+    //! Helpers for the 104 character signatures produced by the `signmessage`
+    //! command of Lightning Network nodes
+    #[cfg(feature = "std")]
+    pub use crate::lightning_impl::encode_signature;
+    pub use crate::lightning_impl::{
+        decode_signature, encode_signature_to_slice, Signature, SIGNATURE_ENCODED_LEN,
+    };
+}
+
+pub mod pkarr {
+    //! Helpers for the 52 character ed25519 public keys used by
+    //! [Pkarr](https://github.com/pubky/pkarr) and the Mainline DHT
+    #[cfg(feature = "std")]
+    pub use crate::pkarr_impl::encode_public_key;
+    pub use crate::pkarr_impl::{
+        decode_public_key, encode_public_key_to_slice, parse_domain_name, parse_uri,
+        PUBLIC_KEY_ENCODED_LEN,
+    };
+}
+
+#[cfg(feature = "rand_core")]
+pub mod random {
+    //! Generation of random values, such as invitation codes or one-time tokens
+    //!
+    //! This module is only available if the "rand_core" feature is enabled.
+    pub use crate::random_impl::generate_to_slice;
+    #[cfg(feature = "std")]
+    pub use crate::random_impl::{generate, generate_with_blocklist};
+}
+
+#[cfg(feature = "tokio")]
+pub mod tokio {
+    //! Adapters that encode and decode data written to or read from
+    //! [tokio](https://docs.rs/tokio) streams
+    //!
+    //! This module is only available if the "tokio" feature is enabled.
+    pub use crate::tokio_impl::{DecoderReader, EncoderWriter};
+}
+
+#[cfg(feature = "uuid")]
+pub mod uuid {
+    //! Encoding and decoding of UUIDs as 26 character values
+    //!
+    //! This module is only available if the "uuid" feature is enabled.
+    pub use crate::uuid_impl::{
+        decode_uuid, encode_uuid_to_slice, ulid_from_parts, UUID_ENCODED_LEN,
+    };
+    #[cfg(feature = "std")]
+    pub use crate::uuid_impl::{encode_uuid, new_ulid};
+}
 
 pub mod low_level_decode {
     //! Low-level decoding functionality
+    #[cfg(feature = "rayon")]
+    pub use crate::decode_impl::par_decode_slices;
     pub use crate::decode_impl::{
-        character_to_quintet, decode_slices, is_last_quintet_valid, quintets_to_octets,
+        character_to_quintet, character_to_quintet_strict, decode_in_place, decode_into_bit_range,
+        decode_iter, decode_slices, decode_slices_strict, decode_slices_with_bit_order,
+        decode_uninit, is_canonical, is_last_quintet_valid, is_last_quintet_valid_with_bit_order,
+        is_valid, possible_bits_for_input, quintets_to_octets, quintets_to_octets_with_bit_order,
+        validate, validate_strict,
     };
-    pub use crate::util::required_octets_buffer_len;
+    pub use crate::util::{octets_for_chars, possible_bits_for_chars, required_octets_buffer_len};
 }
 
 pub mod low_level_encode {
     //! Low-level encoding functionality
+    #[cfg(feature = "rayon")]
+    pub use crate::encode_impl::par_encode_slices;
     pub use crate::encode_impl::{
-        encode_slices, is_last_octet_valid, octets_to_quintets, quintet_to_character,
+        encode_bit_range, encode_in_place, encode_iter, encode_slices,
+        encode_slices_with_bit_order, encode_slices_with_case, encode_uninit, is_last_octet_valid,
+        is_last_octet_valid_with_bit_order, octets_to_quintets, octets_to_quintets_with_bit_order,
+        quintet_to_character, quintet_to_character_with_case,
     };
-    pub use crate::util::required_quintets_buffer_len;
+    pub use crate::util::{qr_alphanumeric_segment_bits, required_quintets_buffer_len};
 }
@@ -0,0 +1,178 @@
+use crate::decode_impl::decode_slices;
+use crate::encode_impl::encode_slices;
+use crate::error::{invalid_format, invalid_length, value_out_of_range};
+use crate::ZBase32Error;
+
+/// The number of characters in an encoded signature.
+pub const SIGNATURE_ENCODED_LEN: usize = 104;
+
+const SIGNATURE_BITS: u64 = 520;
+
+// The header byte is 27 + the recovery id, plus 4 if the public key is compressed.
+const HEADER_MIN: u8 = 27;
+const HEADER_MAX: u8 = 34;
+const HEADER_COMPRESSED: u8 = 4;
+
+/// A 65 byte recoverable signature, as produced by the `signmessage` command
+/// of LND and Core Lightning.
+///
+/// The signature consists of a header byte, which holds the recovery id, followed
+/// by the 64 byte compact signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Signature {
+    header: u8,
+    compact: [u8; 64],
+}
+
+impl Signature {
+    /// Create a Signature from its 65 byte form.
+    ///
+    /// An InputError is returned if the header byte isn't in the range 27-34.
+    pub fn from_bytes(bytes: &[u8; 65]) -> Result<Signature, ZBase32Error> {
+        if !(HEADER_MIN..=HEADER_MAX).contains(&bytes[0]) {
+            return Err(invalid_format());
+        }
+        let mut compact = [0u8; 64];
+        compact.copy_from_slice(&bytes[1..]);
+        Ok(Signature {
+            header: bytes[0],
+            compact,
+        })
+    }
+
+    /// Create a Signature from a recovery id (0-3) and a 64 byte compact signature.
+    ///
+    /// An InputError is returned if `recovery_id` is larger than 3.
+    pub fn from_parts(
+        recovery_id: u8,
+        compressed: bool,
+        compact: [u8; 64],
+    ) -> Result<Signature, ZBase32Error> {
+        if recovery_id > 3 {
+            return Err(value_out_of_range());
+        }
+        let header = HEADER_MIN + recovery_id + if compressed { HEADER_COMPRESSED } else { 0 };
+        Ok(Signature { header, compact })
+    }
+
+    /// Get the 65 byte form of the signature.
+    pub fn to_bytes(&self) -> [u8; 65] {
+        let mut bytes = [0u8; 65];
+        bytes[0] = self.header;
+        bytes[1..].copy_from_slice(&self.compact);
+        bytes
+    }
+
+    /// Get the header byte.
+    pub fn header(&self) -> u8 {
+        self.header
+    }
+
+    /// Get the recovery id (0-3) from the header byte.
+    pub fn recovery_id(&self) -> u8 {
+        (self.header - HEADER_MIN) % HEADER_COMPRESSED
+    }
+
+    /// Determine if the header byte indicates a compressed public key.
+    pub fn is_compressed(&self) -> bool {
+        self.header - HEADER_MIN >= HEADER_COMPRESSED
+    }
+
+    /// Get the 64 byte compact signature.
+    pub fn compact(&self) -> &[u8; 64] {
+        &self.compact
+    }
+}
+
+/// Encode a [`Signature`] to its 104 character form.
+pub fn encode_signature_to_slice(signature: &Signature, out_characters: &mut [u8; 104]) {
+    encode_slices(&signature.to_bytes(), out_characters, SIGNATURE_BITS)
+        .expect("Encoding whole octets can't fail");
+}
+
+/// Encode a [`Signature`] as a 104 character [`String`].
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_signature(signature: &Signature) -> String {
+    let mut buffer = [0u8; SIGNATURE_ENCODED_LEN];
+    encode_signature_to_slice(signature, &mut buffer);
+    buffer.iter().map(|&x| x as char).collect()
+}
+
+/// Decode a [`Signature`] from its 104 character form.
+///
+/// An InputError is returned if `input` isn't exactly 104 characters, contains
+/// an invalid character, or has an invalid header byte.
+pub fn decode_signature(input: &str) -> Result<Signature, ZBase32Error> {
+    if input.len() != SIGNATURE_ENCODED_LEN {
+        return Err(invalid_length());
+    }
+    let mut bytes = [0u8; 65];
+    decode_slices(input.as_bytes(), &mut bytes, SIGNATURE_BITS)?;
+    Signature::from_bytes(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_signature() -> Signature {
+        let mut compact = [0u8; 64];
+        for (index, x) in compact.iter_mut().enumerate() {
+            *x = (index * 37) as u8;
+        }
+        Signature::from_parts(2, true, compact).unwrap()
+    }
+
+    #[test]
+    fn test_signature_parts() {
+        let signature = test_signature();
+        assert_eq!(signature.header(), 33);
+        assert_eq!(signature.recovery_id(), 2);
+        assert!(signature.is_compressed());
+        assert_eq!(signature.compact()[1], 37);
+
+        let bytes = signature.to_bytes();
+        assert_eq!(bytes[0], 33);
+        assert_eq!(Signature::from_bytes(&bytes).unwrap(), signature);
+
+        let signature = Signature::from_parts(1, false, [0; 64]).unwrap();
+        assert_eq!(signature.header(), 28);
+        assert_eq!(signature.recovery_id(), 1);
+        assert!(!signature.is_compressed());
+
+        assert!(Signature::from_parts(4, true, [0; 64]).is_err());
+        let mut bytes = [0u8; 65];
+        bytes[0] = 35;
+        assert!(Signature::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_encode_decode_signature() {
+        let signature = test_signature();
+        let encoded = encode_signature(&signature);
+        assert_eq!(encoded.len(), SIGNATURE_ENCODED_LEN);
+        assert_eq!(decode_signature(&encoded).unwrap(), signature);
+
+        // A header of 31 (0x1f) followed by zeros starts with the quintets 3 and 28
+        let signature = Signature::from_parts(0, true, [0; 64]).unwrap();
+        assert!(encode_signature(&signature).starts_with("dhyy"));
+    }
+
+    #[test]
+    fn test_decode_signature_errors() {
+        let encoded = encode_signature(&test_signature());
+        assert!(matches!(
+            decode_signature(&encoded[1..]),
+            Err(ZBase32Error::InputError(_))
+        ));
+        assert!(matches!(
+            decode_signature(&"y".repeat(104)),
+            Err(ZBase32Error::InputError(_))
+        ));
+        let mut invalid = encoded[..103].to_string();
+        invalid.push('0');
+        assert!(decode_signature(&invalid).is_err());
+    }
+}
@@ -0,0 +1,124 @@
+use crate::decode_impl::decode_slices;
+use crate::encode_impl::encode_slices;
+use crate::error::{invalid_format, invalid_length};
+use crate::ZBase32Error;
+
+/// The number of characters in an encoded public key.
+pub const PUBLIC_KEY_ENCODED_LEN: usize = 52;
+
+const PUBLIC_KEY_BITS: u64 = 256;
+
+const URI_SCHEME: &str = "pk:";
+
+/// Encode a 32 byte ed25519 public key to its 52 character form.
+pub fn encode_public_key_to_slice(key: &[u8; 32], out_characters: &mut [u8; 52]) {
+    encode_slices(key, out_characters, PUBLIC_KEY_BITS).expect("Encoding whole octets can't fail");
+}
+
+/// Encode a 32 byte ed25519 public key as a 52 character [`String`].
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_public_key(key: &[u8; 32]) -> String {
+    let mut buffer = [0u8; PUBLIC_KEY_ENCODED_LEN];
+    encode_public_key_to_slice(key, &mut buffer);
+    buffer.iter().map(|&x| x as char).collect()
+}
+
+/// Decode a 32 byte ed25519 public key from its 52 character form.
+///
+/// An InputError is returned if `input` isn't exactly 52 characters, contains
+/// an invalid character, or if the final 4 bits of the last character are non-zero.
+pub fn decode_public_key(input: &str) -> Result<[u8; 32], ZBase32Error> {
+    if input.len() != PUBLIC_KEY_ENCODED_LEN {
+        return Err(invalid_length());
+    }
+    let mut key = [0u8; 32];
+    decode_slices(input.as_bytes(), &mut key, PUBLIC_KEY_BITS)?;
+    Ok(key)
+}
+
+/// Parse a public key from a URI of the form `pk:<key>`.
+///
+/// The `pk:` scheme is matched case-insensitively.
+pub fn parse_uri(uri: &str) -> Result<[u8; 32], ZBase32Error> {
+    match uri.get(..URI_SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(URI_SCHEME) => {
+            decode_public_key(&uri[URI_SCHEME.len()..])
+        }
+        _ => Err(invalid_format()),
+    }
+}
+
+/// Parse a public key from a DNS-style name, such as `<key>.` or `_label.<key>`.
+///
+/// The key must be the final label of the name. A single trailing `.` is allowed.
+pub fn parse_domain_name(name: &str) -> Result<[u8; 32], ZBase32Error> {
+    let name = name.strip_suffix('.').unwrap_or(name);
+    match name.rsplit('.').next() {
+        Some(label) if !label.is_empty() => decode_public_key(label),
+        _ => Err(invalid_format()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "o4dksfbqk85ogzdb5osziw6befigbuxmuxkuxq8434q89uj56uyy";
+
+    #[test]
+    fn test_encode_decode_public_key() {
+        assert_eq!(encode_public_key(&[0; 32]), "y".repeat(52));
+        assert_eq!(decode_public_key(&"y".repeat(52)).unwrap(), [0; 32]);
+
+        let key = decode_public_key(KEY).unwrap();
+        assert_eq!(encode_public_key(&key), KEY);
+        assert_eq!(decode_public_key(&KEY.to_uppercase()).unwrap(), key);
+    }
+
+    #[test]
+    fn test_decode_public_key_errors() {
+        // Wrong length
+        assert!(matches!(
+            decode_public_key(&KEY[1..]),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Non-zero trailing bits
+        let mut invalid = KEY[..51].to_string();
+        invalid.push('b');
+        assert!(matches!(
+            decode_public_key(&invalid),
+            Err(ZBase32Error::InputError(_))
+        ));
+        // Invalid character
+        let mut invalid = KEY[..51].to_string();
+        invalid.push('l');
+        assert!(matches!(
+            decode_public_key(&invalid),
+            Err(ZBase32Error::InputError(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_uri() {
+        let key = decode_public_key(KEY).unwrap();
+        assert_eq!(parse_uri(&format!("pk:{}", KEY)).unwrap(), key);
+        assert_eq!(parse_uri(&format!("PK:{}", KEY)).unwrap(), key);
+        assert!(parse_uri(KEY).is_err());
+        assert!(parse_uri(&format!("pkarr:{}", KEY)).is_err());
+        assert!(parse_uri("pk").is_err());
+    }
+
+    #[test]
+    fn test_parse_domain_name() {
+        let key = decode_public_key(KEY).unwrap();
+        assert_eq!(parse_domain_name(KEY).unwrap(), key);
+        assert_eq!(parse_domain_name(&format!("{}.", KEY)).unwrap(), key);
+        assert_eq!(parse_domain_name(&format!("_foo.{}", KEY)).unwrap(), key);
+        assert_eq!(parse_domain_name(&format!("a._foo.{}.", KEY)).unwrap(), key);
+        assert!(parse_domain_name(&format!("{}.com", KEY)).is_err());
+        assert!(parse_domain_name(&format!("{}..", KEY)).is_err());
+        assert!(parse_domain_name("").is_err());
+    }
+}
@@ -0,0 +1,185 @@
+use crate::encode_impl::quintet_to_character;
+use crate::error::output_buffer_doesnt_match_bits;
+use crate::util::required_quintets_buffer_len;
+use crate::ZBase32Error;
+use rand_core::RngCore;
+
+/// Generate a random value of `bits` bits and write its encoding to a buffer of characters.
+///
+/// Every possible value is equally likely and the trailing bits of the final
+/// character are always zero, so the output is always valid to decode
+/// with the same value for `bits`. This method does not allocate and is usable
+/// in `no_std` mode.
+///
+/// The length of `out_characters` must match the value returned by
+/// [`required_quintets_buffer_len`].
+pub fn generate_to_slice<R>(
+    bits: u64,
+    rng: &mut R,
+    out_characters: &mut [u8],
+) -> Result<(), ZBase32Error>
+where
+    R: RngCore + ?Sized,
+{
+    if out_characters.len() != required_quintets_buffer_len(bits)? {
+        return Err(output_buffer_doesnt_match_bits().into());
+    }
+
+    // Since 32 divides 256, taking the lowest 5 bits of each random byte
+    // produces uniformly distributed quintets.
+    rng.fill_bytes(out_characters);
+    let last_quintet_bits = match bits % 5 {
+        0 => 5,
+        x => x as u32,
+    };
+    let trailing_bits_mask = 0x1fu8 >> last_quintet_bits;
+    let last_index = out_characters.len().wrapping_sub(1);
+    for (index, character) in out_characters.iter_mut().enumerate() {
+        let mut quintet = *character & 0x1f;
+        if index == last_index {
+            quintet &= !trailing_bits_mask;
+        }
+        *character = quintet_to_character(quintet)?;
+    }
+
+    Ok(())
+}
+
+/// Generate a random value of `bits` bits and return its encoding as a [`String`].
+///
+/// Every possible value is equally likely and the trailing bits of the final
+/// character are always zero.
+///
+/// This method is not available in `no_std` mode.
+///
+/// # Panics
+///
+/// Panics if the number of characters needed for `bits` bits exceeds [`usize::MAX`].
+#[cfg(feature = "std")]
+pub fn generate<R>(bits: u64, rng: &mut R) -> String
+where
+    R: RngCore + ?Sized,
+{
+    let needed_quintets =
+        required_quintets_buffer_len(bits).expect("Too many bits to generate a value");
+    let mut buffer = vec![0; needed_quintets];
+    generate_to_slice(bits, rng, &mut buffer).expect("The buffer is the required size");
+    buffer.into_iter().map(|x| x as char).collect()
+}
+
+/// Generate a random value of `bits` bits whose encoding doesn't contain any of the
+/// entries of `blocklist`.
+///
+/// Values are generated as with [`generate`] until one is found which doesn't
+/// contain any of the entries of `blocklist` (compared case-insensitively). Empty
+/// entries are ignored, since every value would contain them. `None` is returned
+/// if no such value was generated within `max_attempts` attempts.
+///
+/// This method is not available in `no_std` mode.
+///
+/// # Panics
+///
+/// Panics if the number of characters needed for `bits` bits exceeds [`usize::MAX`].
+#[cfg(feature = "std")]
+pub fn generate_with_blocklist<R>(
+    bits: u64,
+    rng: &mut R,
+    blocklist: &[&str],
+    max_attempts: usize,
+) -> Option<String>
+where
+    R: RngCore + ?Sized,
+{
+    let blocklist: Vec<String> = blocklist
+        .iter()
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_ascii_lowercase())
+        .collect();
+    (0..max_attempts)
+        .map(|_| generate(bits, rng))
+        .find(|code| !blocklist.iter().any(|entry| code.contains(entry.as_str())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode_impl::decode;
+
+    // A small xorshift generator so that the tests are deterministic
+    struct TestRng(u64);
+
+    impl RngCore for TestRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            for x in dst {
+                *x = self.next_u64() as u8;
+            }
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut rng = TestRng(0x1234_5678);
+        let mut decoded = Vec::new();
+        for bits in 0..100 {
+            for _ in 0..20 {
+                let code = generate(bits, &mut rng);
+                assert_eq!(code.len(), required_quintets_buffer_len(bits).unwrap());
+                decoded.clear();
+                decode(&code, &mut decoded, bits).unwrap();
+            }
+        }
+
+        let mut buffer = [0u8; 12];
+        generate_to_slice(60, &mut rng, &mut buffer).unwrap();
+        assert!(generate_to_slice(61, &mut rng, &mut buffer).is_err());
+    }
+
+    #[test]
+    fn test_generate_covers_alphabet() {
+        let mut rng = TestRng(42);
+        let mut seen = [false; 256];
+        for _ in 0..1000 {
+            for x in generate(5, &mut rng).bytes() {
+                seen[x as usize] = true;
+            }
+        }
+        assert_eq!(seen.iter().filter(|&&x| x).count(), 32);
+    }
+
+    #[test]
+    fn test_generate_with_blocklist() {
+        let mut rng = TestRng(7);
+        let blocklist: Vec<String> = "ybndrfg8ejkmcpqxot1uwisza345h76"
+            .chars()
+            .map(|x| x.to_ascii_uppercase().to_string())
+            .collect();
+        let blocklist: Vec<&str> = blocklist.iter().map(|x| x.as_str()).collect();
+        assert_eq!(
+            generate_with_blocklist(5, &mut rng, &blocklist, 10_000).unwrap(),
+            "9"
+        );
+
+        let mut blocklist = blocklist;
+        blocklist.push("9");
+        assert_eq!(generate_with_blocklist(5, &mut rng, &blocklist, 100), None);
+    }
+
+    #[test]
+    fn test_generate_with_empty_blocklist_entry() {
+        let mut rng = TestRng(7);
+        let code = generate_with_blocklist(25, &mut rng, &["", "y"], 10_000).unwrap();
+        assert_eq!(code.len(), 5);
+        assert!(!code.contains('y'));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::error::{invalid_quintet, trailing_nonzero_bits};
 use crate::ZBase32Error;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 pub struct NeedQuintets {
     quintet_buffer: [u8; 8],
@@ -7,6 +9,16 @@ pub struct NeedQuintets {
     last_quintet_bits: u8,
 }
 
+// The quintets of the value being decoded are wiped once the state is dropped.
+// Since the states are moved by value, earlier copies of them may remain in
+// memory that isn't wiped.
+#[cfg(feature = "zeroize")]
+impl Drop for NeedQuintets {
+    fn drop(&mut self) {
+        self.quintet_buffer.zeroize();
+    }
+}
+
 pub enum ProvideQuintetResult {
     NeedQuintets(NeedQuintets),
     HaveOctets(HaveOctets),
@@ -29,6 +41,14 @@ impl NeedQuintets {
         }
     }
 
+    // NOTE: panics if `last_quintet_bits` is invalid. This is used when the number
+    // of bits isn't known until the last quintet has been seen.
+    #[cfg(any(feature = "tokio", feature = "futures-io"))]
+    pub fn set_last_quintet_bits(&mut self, last_quintet_bits: u8) {
+        assert!(last_quintet_bits != 0 && last_quintet_bits <= 5);
+        self.last_quintet_bits = last_quintet_bits;
+    }
+
     pub fn provide_quintet(
         mut self,
         quintet: u8,
@@ -80,6 +100,15 @@ pub struct HaveOctets {
     completed: bool,
 }
 
+// The shift register still holds the bits of the octets that haven't been
+// produced yet, so it is wiped as well.
+#[cfg(feature = "zeroize")]
+impl Drop for HaveOctets {
+    fn drop(&mut self) {
+        self.octet_buffer.zeroize();
+    }
+}
+
 pub enum NextOctetResult {
     Octet(u8, HaveOctets),
     NeedQuintets(NeedQuintets),
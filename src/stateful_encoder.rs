@@ -1,5 +1,7 @@
 use crate::error::trailing_nonzero_bits;
 use crate::ZBase32Error;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
 pub struct NeedOctets {
     octet_buffer: [u8; 5],
@@ -7,6 +9,16 @@ pub struct NeedOctets {
     last_octet_bits: u8,
 }
 
+// The octets of the value being encoded are wiped once the state is dropped.
+// Since the states are moved by value, earlier copies of them may remain in
+// memory that isn't wiped.
+#[cfg(feature = "zeroize")]
+impl Drop for NeedOctets {
+    fn drop(&mut self) {
+        self.octet_buffer.zeroize();
+    }
+}
+
 pub enum ProvideOctetResult {
     NeedOctets(NeedOctets),
     HaveQuintets(HaveQuintets),
@@ -72,6 +84,15 @@ pub struct HaveQuintets {
     completed: bool,
 }
 
+// The shift register still holds the bits of the quintets that haven't been
+// produced yet, so it is wiped as well.
+#[cfg(feature = "zeroize")]
+impl Drop for HaveQuintets {
+    fn drop(&mut self) {
+        self.quintet_buffer.zeroize();
+    }
+}
+
 pub enum NextQuintetResult {
     Quintet(u8, HaveQuintets),
     NeedOctets(NeedOctets),
@@ -0,0 +1,359 @@
+use crate::decode_impl::character_to_quintet;
+use crate::encode_impl::quintet_to_character;
+use crate::error::invalid_length;
+use crate::stateful_decoder::{NeedQuintets, NextOctetResult, ProvideQuintetResult};
+use crate::stateful_encoder::{NeedOctets, NextQuintetResult, ProvideOctetResult};
+use crate::util::octets_for_chars;
+use crate::ZBase32Error;
+use std::convert::TryFrom;
+use std::io;
+use std::task::{ready, Poll};
+
+const INPUT_BUFFER_LEN: usize = 1024;
+
+/// Incrementally encodes a stream of octets whose length isn't known until
+/// the stream ends.
+///
+/// Since the stream always consists of whole octets, it can never have non-zero
+/// trailing bits. The most recent octet is held back until either another octet
+/// arrives or the stream is finished, since the state machine needs to know
+/// which octet is the last one.
+pub(crate) struct StreamEncoder {
+    need_octets: Option<NeedOctets>,
+    pending_octet: Option<u8>,
+}
+
+impl Default for StreamEncoder {
+    fn default() -> StreamEncoder {
+        StreamEncoder {
+            need_octets: Some(NeedOctets::new(8)),
+            pending_octet: None,
+        }
+    }
+}
+
+impl StreamEncoder {
+    /// Encode `input`, appending any completed characters to `output`.
+    ///
+    /// Panics if called after [`StreamEncoder::finish`].
+    pub fn encode(&mut self, input: &[u8], output: &mut Vec<u8>) {
+        for &octet in input {
+            if let Some(pending_octet) = self.pending_octet.replace(octet) {
+                self.provide_octet(pending_octet, false, output);
+            }
+        }
+    }
+
+    /// Encode any octets that have been held back, appending the characters
+    /// to `output`.
+    pub fn finish(&mut self, output: &mut Vec<u8>) {
+        if let Some(pending_octet) = self.pending_octet.take() {
+            self.provide_octet(pending_octet, true, output);
+        }
+        self.need_octets = None;
+    }
+
+    fn provide_octet(&mut self, octet: u8, last_octet: bool, output: &mut Vec<u8>) {
+        let need_octets = self.need_octets.take().expect("Encoder already finished");
+        let mut have_quintets = match need_octets
+            .provide_octet(octet, last_octet)
+            .expect("Whole octets can't have non-zero trailing bits")
+        {
+            ProvideOctetResult::NeedOctets(need_octets) => {
+                self.need_octets = Some(need_octets);
+                return;
+            }
+            ProvideOctetResult::HaveQuintets(have_quintets) => have_quintets,
+        };
+        loop {
+            match have_quintets.next_quintet() {
+                NextQuintetResult::Quintet(quintet, next) => {
+                    output.push(quintet_to_character(quintet).expect("Quintets are valid"));
+                    have_quintets = next;
+                }
+                NextQuintetResult::NeedOctets(need_octets) => {
+                    self.need_octets = Some(need_octets);
+                    return;
+                }
+                NextQuintetResult::Complete => return,
+            }
+        }
+    }
+}
+
+/// Incrementally decodes a stream of characters whose length isn't known until
+/// the stream ends.
+///
+/// The number of bits is taken to be the whole number of octets that encodes to
+/// the number of characters in the stream. The most recent character is held back
+/// until either another character arrives or the stream is finished, since the
+/// number of bits in the last character isn't known until then.
+pub(crate) struct StreamDecoder {
+    need_quintets: Option<NeedQuintets>,
+    pending_quintet: Option<u8>,
+    characters: u64,
+}
+
+impl Default for StreamDecoder {
+    fn default() -> StreamDecoder {
+        StreamDecoder {
+            need_quintets: Some(NeedQuintets::new(5)),
+            pending_quintet: None,
+            characters: 0,
+        }
+    }
+}
+
+impl StreamDecoder {
+    /// Decode `input`, appending any completed octets to `output`.
+    ///
+    /// Panics if called after [`StreamDecoder::finish`] or after an Err value
+    /// has been returned.
+    pub fn decode(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<(), ZBase32Error> {
+        for &character in input {
+            let quintet = character_to_quintet(character)?;
+            self.characters += 1;
+            if let Some(pending_quintet) = self.pending_quintet.replace(quintet) {
+                self.provide_quintet(pending_quintet, false, output)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decode any characters that have been held back, appending the octets
+    /// to `output`.
+    ///
+    /// An InputError is returned if the number of characters in the stream doesn't
+    /// correspond to a whole number of octets or if the last character has non-zero
+    /// trailing bits.
+    pub fn finish(&mut self, output: &mut Vec<u8>) -> Result<(), ZBase32Error> {
+        if let Some(pending_quintet) = self.pending_quintet.take() {
+            let octets = usize::try_from(self.characters)
+                .ok()
+                .and_then(octets_for_chars)
+                .ok_or_else(invalid_length)?;
+            let last_quintet_bits = (octets as u64 * 8 - (self.characters - 1) * 5) as u8;
+            if let Some(need_quintets) = self.need_quintets.as_mut() {
+                need_quintets.set_last_quintet_bits(last_quintet_bits);
+            }
+            self.provide_quintet(pending_quintet, true, output)?;
+        }
+        self.need_quintets = None;
+        Ok(())
+    }
+
+    fn provide_quintet(
+        &mut self,
+        quintet: u8,
+        last_quintet: bool,
+        output: &mut Vec<u8>,
+    ) -> Result<(), ZBase32Error> {
+        let need_quintets = self.need_quintets.take().expect("Decoder already finished");
+        let mut have_octets = match need_quintets.provide_quintet(quintet, last_quintet)? {
+            ProvideQuintetResult::NeedQuintets(need_quintets) => {
+                self.need_quintets = Some(need_quintets);
+                return Ok(());
+            }
+            ProvideQuintetResult::HaveOctets(have_octets) => have_octets,
+        };
+        loop {
+            match have_octets.next_octet() {
+                NextOctetResult::Octet(octet, next) => {
+                    output.push(octet);
+                    have_octets = next;
+                }
+                NextOctetResult::NeedQuintets(need_quintets) => {
+                    self.need_quintets = Some(need_quintets);
+                    return Ok(());
+                }
+                NextOctetResult::Complete => return Ok(()),
+            }
+        }
+    }
+}
+
+/// The buffering shared by the `EncoderWriter` adapters, independent of the
+/// async I/O traits.
+///
+/// Encoded characters are buffered until they have all been written to the inner
+/// writer, which is accessed through the `write` function passed to each method.
+#[derive(Default)]
+pub(crate) struct BufferedEncoder {
+    encoder: StreamEncoder,
+    buffer: Vec<u8>,
+    buffer_pos: usize,
+    finished: bool,
+}
+
+impl BufferedEncoder {
+    /// Write any buffered characters using `write`.
+    pub fn poll_write_buffer<F>(&mut self, mut write: F) -> Poll<io::Result<()>>
+    where
+        F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+    {
+        while self.buffer_pos < self.buffer.len() {
+            let written = ready!(write(&self.buffer[self.buffer_pos..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.buffer_pos += written;
+        }
+        self.buffer.clear();
+        self.buffer_pos = 0;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Encode as much of `input` as fits in the buffer once the characters that
+    /// are already buffered have been written using `write`.
+    pub fn poll_write<F>(&mut self, input: &[u8], write: F) -> Poll<io::Result<usize>>
+    where
+        F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+    {
+        if self.finished {
+            return Poll::Ready(Err(io::Error::other(
+                "EncoderWriter has already been finished",
+            )));
+        }
+        ready!(self.poll_write_buffer(write))?;
+        let input = &input[..input.len().min(INPUT_BUFFER_LEN)];
+        self.encoder.encode(input, &mut self.buffer);
+        Poll::Ready(Ok(input.len()))
+    }
+
+    /// Encode the octets that have been held back and write all of the buffered
+    /// characters using `write`.
+    pub fn poll_finish<F>(&mut self, write: F) -> Poll<io::Result<()>>
+    where
+        F: FnMut(&[u8]) -> Poll<io::Result<usize>>,
+    {
+        if !self.finished {
+            self.encoder.finish(&mut self.buffer);
+            self.finished = true;
+        }
+        self.poll_write_buffer(write)
+    }
+}
+
+/// The buffering shared by the `DecoderReader` adapters, independent of the
+/// async I/O traits.
+///
+/// Characters are read from the inner reader through the `read` function passed
+/// to [`BufferedDecoder::poll_read`], and decoded octets are buffered until they
+/// have been copied to the caller.
+pub(crate) struct BufferedDecoder {
+    decoder: StreamDecoder,
+    input: Box<[u8]>,
+    buffer: Vec<u8>,
+    buffer_pos: usize,
+    finished: bool,
+    failed: bool,
+}
+
+impl Default for BufferedDecoder {
+    fn default() -> BufferedDecoder {
+        BufferedDecoder {
+            decoder: StreamDecoder::default(),
+            input: vec![0u8; INPUT_BUFFER_LEN].into_boxed_slice(),
+            buffer: Vec::new(),
+            buffer_pos: 0,
+            finished: false,
+            failed: false,
+        }
+    }
+}
+
+impl BufferedDecoder {
+    /// Copy decoded octets to `output`, reading more characters using `read` if
+    /// none are buffered. `read` returns the number of characters read, where 0
+    /// means that the end of the inner reader was reached.
+    ///
+    /// Decoding errors are reported with the kind [`io::ErrorKind::InvalidData`].
+    /// Once decoding has failed, every later call returns an error as well, so
+    /// that the corrupted input can't be mistaken for the end of the data.
+    pub fn poll_read<F>(&mut self, output: &mut [u8], mut read: F) -> Poll<io::Result<usize>>
+    where
+        F: FnMut(&mut [u8]) -> Poll<io::Result<usize>>,
+    {
+        loop {
+            if self.failed {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "DecoderReader has already failed to decode its input",
+                )));
+            }
+            if self.buffer_pos < self.buffer.len() || output.is_empty() {
+                let available = &self.buffer[self.buffer_pos..];
+                let len = available.len().min(output.len());
+                output[..len].copy_from_slice(&available[..len]);
+                self.buffer_pos += len;
+                return Poll::Ready(Ok(len));
+            }
+            if self.finished {
+                return Poll::Ready(Ok(0));
+            }
+
+            self.buffer.clear();
+            self.buffer_pos = 0;
+            let read = ready!(read(&mut self.input))?;
+            let result = if read == 0 {
+                self.finished = true;
+                self.decoder.finish(&mut self.buffer)
+            } else {
+                self.decoder.decode(&self.input[..read], &mut self.buffer)
+            };
+            if let Err(err) = result {
+                self.buffer.clear();
+                self.failed = true;
+                return Poll::Ready(Err(io::Error::new(io::ErrorKind::InvalidData, err)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+
+    #[test]
+    fn test_stream_round_trip() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            if test.unencoded.len() as u64 * 8 != test.bits {
+                continue;
+            }
+            for chunk_len in 1..8 {
+                let mut encoder = StreamEncoder::default();
+                let mut encoded = Vec::new();
+                for chunk in test.unencoded.chunks(chunk_len) {
+                    encoder.encode(chunk, &mut encoded);
+                }
+                encoder.finish(&mut encoded);
+                assert_eq!(encoded, test.encoded.as_bytes());
+
+                let mut decoder = StreamDecoder::default();
+                let mut decoded = Vec::new();
+                for chunk in test.encoded.as_bytes().chunks(chunk_len) {
+                    decoder.decode(chunk, &mut decoded).unwrap();
+                }
+                decoder.finish(&mut decoded).unwrap();
+                assert_eq!(decoded, test.unencoded);
+            }
+        }
+    }
+
+    #[test]
+    fn test_stream_decoder_errors() {
+        // No whole number of octets encodes to 3 characters
+        let mut decoder = StreamDecoder::default();
+        decoder.decode(b"yyy", &mut Vec::new()).unwrap();
+        assert!(decoder.finish(&mut Vec::new()).is_err());
+
+        // 2 characters encode 1 octet, so the last 2 bits must be zero
+        let mut decoder = StreamDecoder::default();
+        decoder.decode(b"yb", &mut Vec::new()).unwrap();
+        assert!(decoder.finish(&mut Vec::new()).is_err());
+
+        let mut decoder = StreamDecoder::default();
+        assert!(decoder.decode(b"y!", &mut Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,367 @@
+use crate::decode_impl::{decode_slices, validate};
+#[cfg(feature = "std")]
+use crate::encode_impl::encode;
+use crate::error::invalid_length;
+use crate::util::octets_for_chars;
+#[cfg(feature = "std")]
+use crate::util::required_octets_buffer_len;
+use crate::ZBase32Error;
+use core::convert::TryFrom;
+use core::fmt::{Debug, Display, Formatter};
+use core::hash::{Hash, Hasher};
+use core::ops::Deref;
+
+/// A borrowed string that is known to be a valid encoding of a whole number of
+/// octets (bytes).
+///
+/// The number of bits is determined by the length of the string - see
+/// [`octets_for_chars`](crate::low_level_decode::octets_for_chars). Since
+/// both lower-case and upper-case characters may be decoded, equality and hashing
+/// ignore case.
+#[repr(transparent)]
+pub struct ZBase32Str(str);
+
+impl ZBase32Str {
+    fn from_str_unchecked(text: &str) -> &ZBase32Str {
+        // SAFETY: ZBase32Str is a repr(transparent) wrapper around str, so the
+        // pointer cast preserves both the layout and the length metadata.
+        unsafe { &*(text as *const str as *const ZBase32Str) }
+    }
+
+    /// Get the encoded text.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Get the number of bits that the text encodes.
+    pub fn bits(&self) -> u64 {
+        self.decoded_len() as u64 * 8
+    }
+
+    /// Get the number of octets (bytes) that the text decodes to.
+    pub fn decoded_len(&self) -> usize {
+        octets_for_chars(self.0.len()).expect("Length was validated")
+    }
+
+    /// Decode the text into a buffer of octets (bytes).
+    ///
+    /// The length of `out_octets` must match the value returned by
+    /// [`ZBase32Str::decoded_len`].
+    pub fn decode_to_slice(&self, out_octets: &mut [u8]) -> Result<(), ZBase32Error> {
+        decode_slices(self.0.as_bytes(), out_octets, self.bits())
+    }
+
+    /// Decode the text to a [`Vec`] of octets (bytes).
+    ///
+    /// Since the text was validated when the `ZBase32Str` was created, this
+    /// can't fail.
+    ///
+    /// This method is not available in `no_std` mode.
+    #[cfg(feature = "std")]
+    pub fn decode_to_vec(&self) -> Vec<u8> {
+        let mut output = vec![0u8; self.decoded_len()];
+        self.decode_to_slice(&mut output)
+            .expect("Text was validated");
+        output
+    }
+
+    /// Copy the text to a [`ZBase32String`], converting it to lower-case.
+    ///
+    /// This method is not available in `no_std` mode.
+    #[cfg(feature = "std")]
+    pub fn to_zbase32_string(&self) -> ZBase32String {
+        ZBase32String {
+            text: self.0.to_ascii_lowercase(),
+            bits: self.bits(),
+        }
+    }
+}
+
+impl<'a> TryFrom<&'a str> for &'a ZBase32Str {
+    type Error = ZBase32Error;
+
+    /// Validate that `text` is the encoding of a whole number of octets (bytes).
+    fn try_from(text: &'a str) -> Result<&'a ZBase32Str, ZBase32Error> {
+        let octets = octets_for_chars(text.len()).ok_or_else(invalid_length)?;
+        validate(text.as_bytes(), octets as u64 * 8)?;
+        Ok(ZBase32Str::from_str_unchecked(text))
+    }
+}
+
+impl Deref for ZBase32Str {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for ZBase32Str {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for ZBase32Str {
+    fn eq(&self, other: &ZBase32Str) -> bool {
+        self.0.eq_ignore_ascii_case(&other.0)
+    }
+}
+
+impl Eq for ZBase32Str {}
+
+impl Hash for ZBase32Str {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for character in self.0.bytes() {
+            state.write_u8(character.to_ascii_lowercase());
+        }
+        state.write_u8(0xff);
+    }
+}
+
+impl Debug for ZBase32Str {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl Display for ZBase32Str {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// An owned string that is known to be a valid encoding of a specific number
+/// of bits.
+///
+/// The text is always stored in lower-case, so that equality and hashing are
+/// the same regardless of the case of the text that the `ZBase32String` was created
+/// from. Two `ZBase32String` values are equal if both their text and their number
+/// of bits are equal. Since the same text can encode more than one number of bits,
+/// `ZBase32String` doesn't implement `Borrow<str>` - use [`ZBase32String::as_str`]
+/// to compare the text alone.
+///
+/// This type is not available in `no_std` mode.
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ZBase32String {
+    text: String,
+    bits: u64,
+}
+
+#[cfg(feature = "std")]
+impl ZBase32String {
+    /// Validate that `text` is the encoding of a `bits` bit value.
+    ///
+    /// The length of `text` must match the value returned by
+    /// [`required_quintets_buffer_len`](crate::low_level_encode::required_quintets_buffer_len).
+    pub fn new(text: &str, bits: u64) -> Result<ZBase32String, ZBase32Error> {
+        validate(text.as_bytes(), bits)?;
+        Ok(ZBase32String {
+            text: text.to_ascii_lowercase(),
+            bits,
+        })
+    }
+
+    /// Encode a slice of octets (bytes) into a new `ZBase32String`.
+    ///
+    /// The length of `input` must match the value returned by
+    /// [`required_octets_buffer_len`](crate::low_level_decode::required_octets_buffer_len).
+    pub fn encode(input: &[u8], bits: u64) -> Result<ZBase32String, ZBase32Error> {
+        let mut text = String::new();
+        encode(input, &mut text, bits)?;
+        Ok(ZBase32String { text, bits })
+    }
+
+    /// Get the encoded text.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Get the number of bits that the text encodes.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Get the number of octets (bytes) that the text decodes to.
+    pub fn decoded_len(&self) -> usize {
+        required_octets_buffer_len(self.bits).expect("Bits were validated")
+    }
+
+    /// Decode the text to a [`Vec`] of octets (bytes).
+    ///
+    /// Since the text was validated when the `ZBase32String` was created, this
+    /// can't fail.
+    pub fn decode_to_vec(&self) -> Vec<u8> {
+        let mut output = vec![0u8; self.decoded_len()];
+        decode_slices(self.text.as_bytes(), &mut output, self.bits).expect("Text was validated");
+        output
+    }
+
+    /// Get a [`ZBase32Str`] for the text, if it encodes a whole number of
+    /// octets (bytes).
+    pub fn as_zbase32_str(&self) -> Option<&ZBase32Str> {
+        if self.decoded_len() as u64 * 8 == self.bits {
+            Some(ZBase32Str::from_str_unchecked(&self.text))
+        } else {
+            None
+        }
+    }
+
+    /// Consume the `ZBase32String`, returning the lower-case text.
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<&str> for ZBase32String {
+    type Error = ZBase32Error;
+
+    /// Validate that `text` is the encoding of a whole number of octets (bytes).
+    fn try_from(text: &str) -> Result<ZBase32String, ZBase32Error> {
+        <&ZBase32Str>::try_from(text).map(ZBase32Str::to_zbase32_string)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<String> for ZBase32String {
+    type Error = ZBase32Error;
+
+    /// Validate that `text` is the encoding of a whole number of octets (bytes).
+    fn try_from(mut text: String) -> Result<ZBase32String, ZBase32Error> {
+        let bits = <&ZBase32Str>::try_from(text.as_str())?.bits();
+        text.make_ascii_lowercase();
+        Ok(ZBase32String { text, bits })
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<&ZBase32Str> for ZBase32String {
+    fn from(value: &ZBase32Str) -> ZBase32String {
+        value.to_zbase32_string()
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<ZBase32String> for String {
+    fn from(value: ZBase32String) -> String {
+        value.text
+    }
+}
+
+#[cfg(feature = "std")]
+impl Deref for ZBase32String {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(feature = "std")]
+impl AsRef<str> for ZBase32String {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+#[cfg(feature = "std")]
+impl Debug for ZBase32String {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ZBase32String")
+            .field("text", &self.text)
+            .field("bits", &self.bits)
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Display for ZBase32String {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.text, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{HashMap, HashSet};
+
+    fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_zbase32_str() {
+        let value = <&ZBase32Str>::try_from("6n9hq").unwrap();
+        assert_eq!(value.bits(), 24);
+        assert_eq!(value.decoded_len(), 3);
+        assert_eq!(value.decode_to_vec(), [0xf0, 0xbf, 0xc7]);
+        assert_eq!(value.len(), 5);
+        assert_eq!(value.to_string(), "6n9hq");
+
+        let upper = <&ZBase32Str>::try_from("6N9HQ").unwrap();
+        assert_eq!(upper.decode_to_vec(), [0xf0, 0xbf, 0xc7]);
+        assert_eq!(value, upper);
+        assert_eq!(hash(value), hash(upper));
+
+        assert!(<&ZBase32Str>::try_from("")
+            .unwrap()
+            .decode_to_vec()
+            .is_empty());
+        // Invalid length, invalid character, and non-zero trailing bits
+        assert!(<&ZBase32Str>::try_from("6n9").is_err());
+        assert!(<&ZBase32Str>::try_from("6n9h!").is_err());
+        assert!(<&ZBase32Str>::try_from("6n9hb").is_err());
+    }
+
+    #[test]
+    fn test_zbase32_string() {
+        let value = ZBase32String::new("YYSDX", 25).unwrap();
+        assert_eq!(value.as_str(), "yysdx");
+        assert_eq!(value.bits(), 25);
+        assert_eq!(value.decoded_len(), 4);
+        assert_eq!(value.decode_to_vec(), [0, 44, 55, 128]);
+        assert!(value.as_zbase32_str().is_none());
+        assert_eq!(value, ZBase32String::encode(&[0, 44, 55, 128], 25).unwrap());
+        assert!(ZBase32String::new("yysdx", 24).is_err());
+        // The same text holding a different number of bits is a different value
+        assert_ne!(
+            ZBase32String::new("yy", 8).unwrap(),
+            ZBase32String::new("yy", 10).unwrap()
+        );
+
+        let value = ZBase32String::try_from("6N9HQ").unwrap();
+        assert_eq!(&*value, "6n9hq");
+        assert_eq!(value.bits(), 24);
+        assert_eq!(
+            value.as_zbase32_str().unwrap().decode_to_vec(),
+            [0xf0, 0xbf, 0xc7]
+        );
+        assert_eq!(value, ZBase32String::try_from("6n9hq".to_string()).unwrap());
+        assert_eq!(
+            hash(&value),
+            hash(&ZBase32String::new("6N9HQ", 24).unwrap())
+        );
+        assert!(ZBase32String::try_from("6n9").is_err());
+    }
+
+    #[test]
+    fn test_map_lookup() {
+        let mut map = HashMap::new();
+        map.insert(ZBase32String::try_from("6N9HQ").unwrap(), 1);
+        assert_eq!(map.get(&ZBase32String::new("6n9hq", 24).unwrap()), Some(&1));
+        map.insert(ZBase32String::new("yy", 8).unwrap(), 2);
+        assert_eq!(map.get(&ZBase32String::new("yy", 10).unwrap()), None);
+        map.insert(ZBase32String::try_from("6n9hq").unwrap(), 3);
+        assert_eq!(map.len(), 2);
+
+        let mut set = HashSet::new();
+        set.insert(<&ZBase32Str>::try_from("6n9hq").unwrap());
+        assert!(set.contains(<&ZBase32Str>::try_from("6N9HQ").unwrap()));
+    }
+}
@@ -0,0 +1,216 @@
+use crate::stream_impl::{BufferedDecoder, BufferedEncoder};
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// An [`AsyncWrite`] adapter that encodes the octets (bytes) written to it and
+/// writes the resulting characters to an inner writer.
+///
+/// The number of bits is always a whole number of octets. Since the final group of
+/// up to 5 octets can't be encoded until the end of the data is known, it is only
+/// written once the `EncoderWriter` is shut down. Flushing an `EncoderWriter` only
+/// writes characters for the octets that have been encoded so far.
+pub struct EncoderWriter<W> {
+    inner: W,
+    encoder: BufferedEncoder,
+}
+
+impl<W> EncoderWriter<W> {
+    /// Create a new `EncoderWriter` that writes characters to `inner`.
+    pub fn new(inner: W) -> EncoderWriter<W> {
+        EncoderWriter {
+            inner,
+            encoder: BufferedEncoder::default(),
+        }
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consume this `EncoderWriter`, returning the inner writer.
+    ///
+    /// Any octets that haven't been written to the inner writer yet are lost.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for EncoderWriter<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let inner = &mut this.inner;
+        this.encoder
+            .poll_write(buf, |data| Pin::new(&mut *inner).poll_write(cx, data))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let inner = &mut this.inner;
+        ready!(this
+            .encoder
+            .poll_write_buffer(|data| Pin::new(&mut *inner).poll_write(cx, data)))?;
+        Pin::new(inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let inner = &mut this.inner;
+        ready!(this
+            .encoder
+            .poll_finish(|data| Pin::new(&mut *inner).poll_write(cx, data)))?;
+        Pin::new(inner).poll_shutdown(cx)
+    }
+}
+
+/// An [`AsyncRead`] adapter that reads characters from an inner reader and
+/// decodes them into octets (bytes).
+///
+/// The inner reader must only produce z-base-32 characters - any other character
+/// results in an error with the kind [`io::ErrorKind::InvalidData`], as does every
+/// later read. The number of bits is taken to be the whole number of octets that
+/// encodes to the number of characters read before the end of the inner reader.
+pub struct DecoderReader<R> {
+    inner: R,
+    decoder: BufferedDecoder,
+}
+
+impl<R> DecoderReader<R> {
+    /// Create a new `DecoderReader` that reads characters from `inner`.
+    pub fn new(inner: R) -> DecoderReader<R> {
+        DecoderReader {
+            inner,
+            decoder: BufferedDecoder::default(),
+        }
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Consume this `DecoderReader`, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for DecoderReader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let inner = &mut this.inner;
+        let len = ready!(this.decoder.poll_read(buf.initialize_unfilled(), |input| {
+            let mut input = ReadBuf::new(input);
+            ready!(Pin::new(&mut *inner).poll_read(cx, &mut input))?;
+            Poll::Ready(Ok(input.filled().len()))
+        }))?;
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+    use tokio::io::{duplex, AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_encoder_writer() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            if test.unencoded.len() as u64 * 8 != test.bits {
+                continue;
+            }
+            let (client, mut server) = duplex(7);
+            let write = async {
+                let mut writer = EncoderWriter::new(client);
+                for chunk in test.unencoded.chunks(3) {
+                    writer.write_all(chunk).await.unwrap();
+                }
+                writer.shutdown().await.unwrap();
+            };
+            let read = async {
+                let mut encoded = String::new();
+                server.read_to_string(&mut encoded).await.unwrap();
+                encoded
+            };
+            let (_, encoded) = tokio::join!(write, read);
+            assert_eq!(encoded, test.encoded);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decoder_reader() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            if test.unencoded.len() as u64 * 8 != test.bits {
+                continue;
+            }
+            let (mut client, server) = duplex(7);
+            let write = async {
+                client.write_all(test.encoded.as_bytes()).await.unwrap();
+                client.shutdown().await.unwrap();
+            };
+            let read = async {
+                let mut decoded = Vec::new();
+                DecoderReader::new(server)
+                    .read_to_end(&mut decoded)
+                    .await
+                    .unwrap();
+                decoded
+            };
+            let (_, decoded) = tokio::join!(write, read);
+            assert_eq!(decoded, test.unencoded);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplex_partial_reads() {
+        let data: Vec<u8> = (0..=255).collect();
+        for chunk_len in [1, 3, 7, 1000].iter().copied() {
+            let (client, server) = duplex(3);
+            let write = async {
+                let mut writer = EncoderWriter::new(client);
+                for chunk in data.chunks(chunk_len) {
+                    writer.write_all(chunk).await.unwrap();
+                }
+                writer.shutdown().await.unwrap();
+            };
+            let read = async {
+                let mut decoded = Vec::new();
+                let mut reader = DecoderReader::new(server);
+                let mut chunk = vec![0u8; chunk_len];
+                loop {
+                    match reader.read(&mut chunk).await.unwrap() {
+                        0 => break,
+                        len => decoded.extend_from_slice(&chunk[..len]),
+                    }
+                }
+                decoded
+            };
+            let (_, decoded) = tokio::join!(write, read);
+            assert_eq!(decoded, data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decoder_reader_errors() {
+        for encoded in ["yyy", "yb", "yy!"].iter() {
+            let mut reader = DecoderReader::new(encoded.as_bytes());
+            let err = reader.read_to_end(&mut Vec::new()).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            // Retrying doesn't produce a clean end of file
+            let err = reader.read(&mut [0; 8]).await.unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(feature = "std")]
+use crate::decode_impl::decode_bytes_input;
+use crate::decode_impl::decode_slices;
+#[cfg(feature = "std")]
+use crate::encode_impl::encode_to_string;
+use crate::error::{invalid_length, output_buffer_doesnt_match_bits};
+#[cfg(feature = "std")]
+use crate::util::octets_for_chars;
+use crate::util::required_octets_buffer_len;
+use crate::ZBase32Error;
+
+/// Encoding of byte containers.
+///
+/// This trait is implemented for `[u8]`, `[u8; N]`, and `Vec<u8>`, as well as for
+/// `bytes::Bytes` if the "bytes" feature is enabled. It isn't implemented for `str`
+/// or `String`, since those usually hold text that is already encoded.
+///
+/// Example:
+///
+/// ```
+/// use libzbase32::ToZBase32;
+///
+/// assert_eq!([0xf0, 0xbf, 0xc7].to_zbase32(), "6n9hq");
+/// assert_eq!([0, 44, 55, 128].to_zbase32_bits(25).unwrap(), "yysdx");
+/// ```
+///
+/// This trait is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub trait ToZBase32 {
+    /// Encode all of the octets (bytes) of this value to a [`String`].
+    fn to_zbase32(&self) -> String;
+
+    /// Encode the first `bits` bits of this value to a [`String`].
+    ///
+    /// The length of the value must match the value returned by
+    /// [`required_octets_buffer_len`], and any bits of the last octet past
+    /// `bits` must be zero.
+    fn to_zbase32_bits(&self, bits: u64) -> Result<String, ZBase32Error>;
+}
+
+#[cfg(feature = "std")]
+impl ToZBase32 for [u8] {
+    fn to_zbase32(&self) -> String {
+        let bits = self.len() as u64 * 8;
+        encode_to_string(self, bits).expect("Whole octets can always be encoded")
+    }
+
+    fn to_zbase32_bits(&self, bits: u64) -> Result<String, ZBase32Error> {
+        encode_to_string(self, bits)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const N: usize> ToZBase32 for [u8; N] {
+    fn to_zbase32(&self) -> String {
+        self[..].to_zbase32()
+    }
+
+    fn to_zbase32_bits(&self, bits: u64) -> Result<String, ZBase32Error> {
+        self[..].to_zbase32_bits(bits)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ToZBase32 for Vec<u8> {
+    fn to_zbase32(&self) -> String {
+        self[..].to_zbase32()
+    }
+
+    fn to_zbase32_bits(&self, bits: u64) -> Result<String, ZBase32Error> {
+        self[..].to_zbase32_bits(bits)
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl ToZBase32 for bytes::Bytes {
+    fn to_zbase32(&self) -> String {
+        self[..].to_zbase32()
+    }
+
+    fn to_zbase32_bits(&self, bits: u64) -> Result<String, ZBase32Error> {
+        self[..].to_zbase32_bits(bits)
+    }
+}
+
+/// Decoding into byte containers, such as `[u8; N]` or `Vec<u8>`.
+///
+/// Example:
+///
+/// ```
+/// use libzbase32::FromZBase32;
+///
+/// assert_eq!(<[u8; 3]>::from_zbase32("6n9hq").unwrap(), [0xf0, 0xbf, 0xc7]);
+/// assert!(<[u8; 4]>::from_zbase32("6n9hq").is_err());
+/// ```
+pub trait FromZBase32: Sized {
+    /// Decode a value that encodes a whole number of octets (bytes).
+    ///
+    /// For a fixed size array, the input must encode exactly as many octets as
+    /// the array holds. Otherwise, the number of octets is determined from the
+    /// length of the input, as with
+    /// [`octets_for_chars`](crate::low_level_decode::octets_for_chars).
+    fn from_zbase32<T: AsRef<[u8]>>(input: T) -> Result<Self, ZBase32Error>;
+
+    /// Decode a value that encodes `bits` bits.
+    ///
+    /// For a fixed size array, the length of the array must match the value
+    /// returned by [`required_octets_buffer_len`].
+    fn from_zbase32_bits<T: AsRef<[u8]>>(input: T, bits: u64) -> Result<Self, ZBase32Error>;
+}
+
+impl<const N: usize> FromZBase32 for [u8; N] {
+    fn from_zbase32<T: AsRef<[u8]>>(input: T) -> Result<Self, ZBase32Error> {
+        let bits = (N as u64).checked_mul(8).ok_or_else(invalid_length)?;
+        Self::from_zbase32_bits(input, bits)
+    }
+
+    fn from_zbase32_bits<T: AsRef<[u8]>>(input: T, bits: u64) -> Result<Self, ZBase32Error> {
+        if required_octets_buffer_len(bits)? != N {
+            return Err(output_buffer_doesnt_match_bits().into());
+        }
+        let mut output = [0u8; N];
+        decode_slices(input.as_ref(), &mut output, bits)?;
+        Ok(output)
+    }
+}
+
+#[cfg(feature = "std")]
+impl FromZBase32 for Vec<u8> {
+    fn from_zbase32<T: AsRef<[u8]>>(input: T) -> Result<Self, ZBase32Error> {
+        let input = input.as_ref();
+        let octets = octets_for_chars(input.len()).ok_or_else(invalid_length)?;
+        Self::from_zbase32_bits(input, octets as u64 * 8)
+    }
+
+    fn from_zbase32_bits<T: AsRef<[u8]>>(input: T, bits: u64) -> Result<Self, ZBase32Error> {
+        let mut output = Vec::new();
+        decode_bytes_input(input.as_ref(), &mut output, bits)?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_data::{RANDOM_TEST_DATA, STANDARD_TEST_DATA};
+
+    #[test]
+    fn test_to_zbase32() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            assert_eq!(
+                test.unencoded.to_zbase32_bits(test.bits).unwrap(),
+                test.encoded
+            );
+            if test.unencoded.len() as u64 * 8 == test.bits {
+                assert_eq!(test.unencoded.to_zbase32(), test.encoded);
+                assert_eq!(test.unencoded.to_vec().to_zbase32(), test.encoded);
+            }
+        }
+        assert_eq!([0u8; 0].to_zbase32(), "");
+        assert_eq!([0u8; 1].to_zbase32(), "yy");
+        assert!([1u8].to_zbase32_bits(7).is_err());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn test_bytes_to_zbase32() {
+        let value = bytes::Bytes::from_static(&[0xf0, 0xbf, 0xc7]);
+        assert_eq!(value.to_zbase32(), "6n9hq");
+        assert_eq!(value.to_zbase32_bits(24).unwrap(), "6n9hq");
+    }
+
+    #[test]
+    fn test_from_zbase32() {
+        for test in STANDARD_TEST_DATA.iter().chain(RANDOM_TEST_DATA) {
+            let decoded = Vec::<u8>::from_zbase32_bits(test.encoded, test.bits).unwrap();
+            assert_eq!(decoded, test.unencoded);
+            if test.unencoded.len() as u64 * 8 == test.bits {
+                assert_eq!(
+                    Vec::<u8>::from_zbase32(test.encoded).unwrap(),
+                    test.unencoded
+                );
+            }
+        }
+
+        assert_eq!(
+            <[u8; 4]>::from_zbase32_bits("yysdx", 25).unwrap(),
+            [0, 44, 55, 128]
+        );
+        assert!(<[u8; 3]>::from_zbase32_bits("yysdx", 25).is_err());
+        assert!(<[u8; 1]>::from_zbase32("yb").is_err());
+        assert!(<[u8; 0]>::from_zbase32("").is_ok());
+        assert!(Vec::<u8>::from_zbase32("yyy").is_err());
+        assert_eq!(
+            Vec::<u8>::from_zbase32(b"6N9HQ").unwrap(),
+            [0xf0, 0xbf, 0xc7]
+        );
+    }
+}
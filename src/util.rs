@@ -1,5 +1,20 @@
-use crate::error::bits_overflow;
+use crate::error::{bit_range_out_of_bounds, bits_overflow, input_buffer_doesnt_match_bits};
 use crate::UsageError;
+use core::mem::MaybeUninit;
+use core::ops::RangeInclusive;
+
+/// The number of octets in a single 40-bit group. Each group encodes to
+/// exactly [`QUINTETS_PER_GROUP`] quintets.
+#[cfg(feature = "rayon")]
+pub const OCTETS_PER_GROUP: usize = 5;
+
+/// The number of quintets in a single 40-bit group.
+#[cfg(feature = "rayon")]
+pub const QUINTETS_PER_GROUP: usize = 8;
+
+/// The number of 40-bit groups processed by each parallel task.
+#[cfg(feature = "rayon")]
+pub const PAR_CHUNK_GROUPS: usize = 4096;
 
 const fn u64_to_usize(val: u64) -> Option<usize> {
     if usize::BITS >= u64::BITS {
@@ -45,3 +60,172 @@ pub const fn required_quintets_buffer_len(bits: u64) -> Result<usize, UsageError
         Err(bits_overflow())
     }
 }
+
+/// Calculate the range of bit counts whose encoding is exactly `characters`
+/// characters long.
+///
+/// This is the inverse of [`required_quintets_buffer_len`]. For example, 8 characters
+/// may hold anywhere from 36 to 40 bits. An empty input may only hold 0 bits.
+///
+/// This function will return an Err value if `characters` characters would
+/// hold more than [`u64::MAX`] bits.
+pub const fn possible_bits_for_chars(characters: usize) -> Result<RangeInclusive<u64>, UsageError> {
+    if characters == 0 {
+        Ok(0..=0)
+    } else if let Some(max_bits) = (characters as u64).checked_mul(5) {
+        Ok((max_bits - 4)..=max_bits)
+    } else {
+        Err(bits_overflow())
+    }
+}
+
+/// Calculate the number of octets (bytes) whose encoding is exactly `characters`
+/// characters long.
+///
+/// At most one whole number of octets can be encoded into any given number of
+/// characters. `None` is returned if there is no such number - for example, no
+/// whole number of octets encodes into 3 characters.
+pub const fn octets_for_chars(characters: usize) -> Option<usize> {
+    let range = match possible_bits_for_chars(characters) {
+        Ok(range) => range,
+        Err(_) => return None,
+    };
+    let octets = *range.end() / 8;
+    if octets * 8 >= *range.start() {
+        u64_to_usize(octets)
+    } else {
+        None
+    }
+}
+
+/// Calculate the number of data bits needed to store the upper-case encoding of
+/// the specified number of bits in a QR code alphanumeric mode segment.
+///
+/// Upper-case z-base-32 characters (see [`Case`](crate::Case)) are all part of
+/// the QR code alphanumeric character set, which stores each pair of characters
+/// in 11 bits, and a final unpaired character in 6 bits. The returned value
+/// doesn't include the mode indicator or the character count indicator, whose
+/// sizes depend on the QR code version.
+///
+/// This function will return an Err value if the specified number of bits
+/// would result in needing more than [`usize::MAX`] quintets.
+pub const fn qr_alphanumeric_segment_bits(bits: u64) -> Result<u64, UsageError> {
+    match required_quintets_buffer_len(bits) {
+        Ok(characters) => {
+            let characters = characters as u64;
+            Ok(characters / 2 * 11 + characters % 2 * 6)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Check that the range of `bits` bits starting at bit `start_bit` fits within
+/// a buffer of `buffer_len` octets.
+pub fn check_bit_range(buffer_len: usize, start_bit: u64, bits: u64) -> Result<(), UsageError> {
+    let buffer_bits = (buffer_len as u64).checked_mul(8);
+    match (start_bit.checked_add(bits), buffer_bits) {
+        (Some(end_bit), Some(buffer_bits)) if end_bit <= buffer_bits => Ok(()),
+        _ => Err(bit_range_out_of_bounds()),
+    }
+}
+
+/// Read the 8 bits starting at bit `bit` of `octets`, where bit 0 is the
+/// highest bit of the first octet. Bits past the end of `octets` are read as zero.
+pub fn read_octet_at_bit(octets: &[u8], bit: u64) -> u8 {
+    let index = (bit / 8) as usize;
+    let shift = (bit % 8) as u32;
+    let high = octets[index] << shift;
+    let low = if shift == 0 {
+        0
+    } else {
+        octets.get(index + 1).map_or(0, |&x| x >> (8 - shift))
+    };
+    high | low
+}
+
+/// Write the highest `count` bits of `value` into `octets` starting at bit `bit`,
+/// where bit 0 is the highest bit of the first octet. All other bits of `octets`
+/// are left unmodified.
+pub fn write_octet_at_bit(octets: &mut [u8], bit: u64, value: u8, count: u8) {
+    let index = (bit / 8) as usize;
+    let shift = (bit % 8) as u32;
+    let mask = !0xffu8.checked_shr(count as u32).unwrap_or(0);
+    let value = value & mask;
+    octets[index] = (octets[index] & !(mask >> shift)) | (value >> shift);
+    if shift + count as u32 > 8 {
+        octets[index + 1] = (octets[index + 1] & !(mask << (8 - shift))) | (value << (8 - shift));
+    }
+}
+
+/// Fill `output` with the items produced by `iter`, returning the initialized
+/// slice once every element has been written.
+///
+/// An Err value is returned if `iter` produces an error or if it doesn't produce
+/// at least `output.len()` items. In either case, `output` may have been
+/// partially written.
+pub fn fill_uninit<I, E>(output: &mut [MaybeUninit<u8>], iter: I) -> Result<&mut [u8], E>
+where
+    I: Iterator<Item = Result<u8, E>>,
+    E: From<UsageError>,
+{
+    let mut written = 0;
+    for (out, next) in output.iter_mut().zip(iter) {
+        out.write(next?);
+        written += 1;
+    }
+    if written != output.len() {
+        return Err(input_buffer_doesnt_match_bits().into());
+    }
+    // SAFETY: every element of `output` was written by the loop above, and
+    // MaybeUninit<u8> has the same layout as u8.
+    Ok(unsafe { &mut *(output as *mut [MaybeUninit<u8>] as *mut [u8]) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{octets_for_chars, possible_bits_for_chars, qr_alphanumeric_segment_bits};
+    use crate::util::{required_octets_buffer_len, required_quintets_buffer_len};
+
+    #[test]
+    fn test_possible_bits_for_chars() {
+        assert_eq!(possible_bits_for_chars(0).unwrap(), 0..=0);
+        assert_eq!(possible_bits_for_chars(1).unwrap(), 1..=5);
+        assert_eq!(possible_bits_for_chars(8).unwrap(), 36..=40);
+        for bits in 0..1000 {
+            let characters = required_quintets_buffer_len(bits).unwrap();
+            assert!(possible_bits_for_chars(characters).unwrap().contains(&bits));
+        }
+        if usize::MAX as u64 > u64::MAX / 5 {
+            assert!(possible_bits_for_chars(usize::MAX).is_err());
+            assert_eq!(octets_for_chars(usize::MAX), None);
+        }
+    }
+
+    #[test]
+    fn test_octets_for_chars() {
+        assert_eq!(octets_for_chars(0), Some(0));
+        assert_eq!(octets_for_chars(1), None);
+        assert_eq!(octets_for_chars(2), Some(1));
+        assert_eq!(octets_for_chars(3), None);
+        assert_eq!(octets_for_chars(8), Some(5));
+        assert_eq!(octets_for_chars(52), Some(32));
+        for octets in 0..200usize {
+            let characters = required_quintets_buffer_len(octets as u64 * 8).unwrap();
+            assert_eq!(octets_for_chars(characters), Some(octets));
+            assert_eq!(
+                required_octets_buffer_len(octets as u64 * 8).unwrap(),
+                octets
+            );
+        }
+    }
+
+    #[test]
+    fn test_qr_alphanumeric_segment_bits() {
+        assert_eq!(qr_alphanumeric_segment_bits(0).unwrap(), 0);
+        assert_eq!(qr_alphanumeric_segment_bits(5).unwrap(), 6);
+        assert_eq!(qr_alphanumeric_segment_bits(10).unwrap(), 11);
+        assert_eq!(qr_alphanumeric_segment_bits(128).unwrap(), 13 * 11);
+        assert_eq!(qr_alphanumeric_segment_bits(255).unwrap(), 25 * 11 + 6);
+        assert_eq!(qr_alphanumeric_segment_bits(256).unwrap(), 26 * 11);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::error::value_out_of_range;
+#[cfg(feature = "std")]
+use crate::integer_impl::encode_u128;
+use crate::integer_impl::{decode_u128, encode_u128_to_slice};
+use crate::ZBase32Error;
+use uuid::Uuid;
+
+/// The number of characters in an encoded UUID.
+pub const UUID_ENCODED_LEN: usize = 26;
+
+const UUID_BITS: u64 = 128;
+
+/// Encode a [`Uuid`] to a buffer of characters.
+///
+/// The length of `out_characters` must be [`UUID_ENCODED_LEN`].
+pub fn encode_uuid_to_slice(uuid: &Uuid, out_characters: &mut [u8]) -> Result<(), ZBase32Error> {
+    encode_u128_to_slice(uuid.as_u128(), UUID_BITS, out_characters)
+}
+
+/// Encode a [`Uuid`] as a 26 character [`String`].
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn encode_uuid(uuid: &Uuid) -> String {
+    encode_u128(uuid.as_u128(), UUID_BITS).expect("A UUID always fits in 128 bits")
+}
+
+/// Decode a [`Uuid`] from its 26 character encoding.
+///
+/// The 26 characters hold 130 bits, so an Err value is returned if the final
+/// 2 bits are non-zero.
+pub fn decode_uuid(input: &str) -> Result<Uuid, ZBase32Error> {
+    decode_u128(input, UUID_BITS).map(Uuid::from_u128)
+}
+
+/// Build a ULID-style [`Uuid`] from a 48-bit millisecond timestamp and 80 random bits.
+///
+/// The timestamp is stored in the highest 48 bits followed by the random bits.
+/// An Err value is returned if `timestamp_ms` doesn't fit in 48 bits.
+pub fn ulid_from_parts(timestamp_ms: u64, random: [u8; 10]) -> Result<Uuid, ZBase32Error> {
+    if timestamp_ms >> 48 != 0 {
+        return Err(value_out_of_range());
+    }
+    let mut bytes = [0u8; 16];
+    bytes[..6].copy_from_slice(&timestamp_ms.to_be_bytes()[2..]);
+    bytes[6..].copy_from_slice(&random);
+    Ok(Uuid::from_bytes(bytes))
+}
+
+/// Generate a new ULID-style [`Uuid`] using the current time and 80 random bits.
+///
+/// The random bits are taken from a version 4 UUID generated by the uuid crate.
+/// ULIDs generated within the same millisecond are not guaranteed to be ordered.
+///
+/// This method is not available in `no_std` mode.
+#[cfg(feature = "std")]
+pub fn new_ulid() -> Uuid {
+    let timestamp_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |x| x.as_millis() as u64)
+        & 0xffff_ffff_ffff;
+
+    // Only the version and variant bits (in octets 6 and 8) of a version 4
+    // UUID aren't random.
+    let source = *Uuid::new_v4().as_bytes();
+    let mut random = [0u8; 10];
+    random[..6].copy_from_slice(&source[..6]);
+    random[6..].copy_from_slice(&source[9..13]);
+
+    ulid_from_parts(timestamp_ms, random).expect("The timestamp was masked to 48 bits")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_uuid() {
+        assert_eq!(encode_uuid(&Uuid::nil()), "yyyyyyyyyyyyyyyyyyyyyyyyyy");
+        assert_eq!(encode_uuid(&Uuid::max()), "9999999999999999999999999h");
+
+        let uuid = Uuid::from_u128(0x6ba7b810_9dad_11d1_80b4_00c04fd430c8);
+        let encoded = encode_uuid(&uuid);
+        assert_eq!(encoded.len(), UUID_ENCODED_LEN);
+        assert_eq!(decode_uuid(&encoded).unwrap(), uuid);
+        assert_eq!(decode_uuid(&encoded.to_uppercase()).unwrap(), uuid);
+
+        let mut buffer = [0u8; UUID_ENCODED_LEN];
+        encode_uuid_to_slice(&uuid, &mut buffer).unwrap();
+        assert_eq!(&buffer[..], encoded.as_bytes());
+    }
+
+    #[test]
+    fn test_decode_uuid_errors() {
+        // Non-zero trailing bits
+        assert!(matches!(
+            decode_uuid("yyyyyyyyyyyyyyyyyyyyyyyyyb"),
+            Err(ZBase32Error::InputError(_))
+        ));
+        assert!(decode_uuid("yyyyyyyyyyyyyyyyyyyyyyyyy").is_err());
+        assert!(decode_uuid("yyyyyyyyyyyyyyyyyyyyyyyyy!").is_err());
+    }
+
+    #[test]
+    fn test_ulid() {
+        let ulid = ulid_from_parts(0x0123_4567_89ab, [0xff; 10]).unwrap();
+        assert_eq!(ulid.as_u128(), 0x0123_4567_89ab_ffff_ffff_ffff_ffff_ffff);
+        assert!(ulid_from_parts(1 << 48, [0; 10]).is_err());
+
+        // 2020-01-01T00:00:00Z
+        let ulid = new_ulid();
+        assert!(ulid.as_u128() >> 80 > 1_577_836_800_000);
+        assert_eq!(decode_uuid(&encode_uuid(&ulid)).unwrap(), ulid);
+    }
+}